@@ -9,31 +9,64 @@ compile_error!("target arch should be wasm32: compile with '--target wasm32-unkn
 extern crate alloc;
 
 use casper_contract::contract_api::{account, runtime, system};
+use casper_contract::ext_ffi;
 use casper_contract::unwrap_or_revert::UnwrapOrRevert;
-use casper_types::account::AccountHash;
-use casper_types::{runtime_args, ContractHash, RuntimeArgs, URef};
+use casper_types::{api_error, runtime_args, ApiError, ContractHash, Key, RuntimeArgs, URef};
 
 const ENTRY_POINT_GET_PURSE: &str = "get_purse";
 const ENTRY_POINT_DEPOSIT: &str = "deposit";
+const ENTRY_POINT_DEPOSIT_WALLET: &str = "deposit_wallet";
 const ARG_RELAY_CONTRACT: &str = "relay_contract";
 const ARG_AMOUNT: &str = "amount";
 const ARG_OWNER: &str = "owner";
+const ARG_PURSE: &str = "purse";
 
 #[no_mangle]
 pub extern "C" fn call() {
     let relay_contract: ContractHash = runtime::get_named_arg(ARG_RELAY_CONTRACT);
     let amount = runtime::get_named_arg(ARG_AMOUNT);
-    let owner: AccountHash = runtime::get_named_arg(ARG_OWNER);
-    let purse: URef =
-        runtime::call_contract(relay_contract, ENTRY_POINT_GET_PURSE, RuntimeArgs::new());
-    system::transfer_from_purse_to_purse(account::get_main_purse(), purse, amount, None)
-        .unwrap_or_revert();
 
-    let _: () = runtime::call_contract(
-        relay_contract,
-        ENTRY_POINT_DEPOSIT,
-        runtime_args! {
-            ARG_OWNER => owner,
-        },
-    );
+    // Without an owner the deposit goes to the sender's own relay wallet
+    if has_named_arg(ARG_OWNER) {
+        let owner: Key = runtime::get_named_arg(ARG_OWNER);
+        let purse: URef =
+            runtime::call_contract(relay_contract, ENTRY_POINT_GET_PURSE, RuntimeArgs::new());
+        system::transfer_from_purse_to_purse(account::get_main_purse(), purse, amount, None)
+            .unwrap_or_revert();
+        let _: () = runtime::call_contract(
+            relay_contract,
+            ENTRY_POINT_DEPOSIT,
+            runtime_args! {
+                ARG_OWNER => owner,
+            },
+        );
+    } else {
+        // The wallet deposit travels in a purse of its own
+        let purse = system::create_purse();
+        system::transfer_from_purse_to_purse(account::get_main_purse(), purse, amount, None)
+            .unwrap_or_revert();
+        let _: () = runtime::call_contract(
+            relay_contract,
+            ENTRY_POINT_DEPOSIT_WALLET,
+            runtime_args! {
+                ARG_PURSE => purse,
+            },
+        );
+    }
+}
+
+fn has_named_arg(name: &str) -> bool {
+    let mut arg_size: usize = 0;
+    let ret = unsafe {
+        ext_ffi::casper_get_named_arg_size(
+            name.as_bytes().as_ptr(),
+            name.len(),
+            &mut arg_size as *mut usize,
+        )
+    };
+    match api_error::result_from(ret) {
+        Ok(_) => true,
+        Err(ApiError::MissingArgument) => false,
+        Err(e) => runtime::revert(e),
+    }
 }
@@ -0,0 +1,124 @@
+#![no_std]
+#![no_main]
+
+#[cfg(not(target_arch = "wasm32"))]
+compile_error!("target arch should be wasm32: compile with '--target wasm32-unknown-unknown'");
+
+// We need to explicitly import the std alloc crate and `alloc::string::String` as we're in a
+// `no_std` environment.
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use casper_contract::contract_api::{runtime, storage};
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+use casper_types::bytesrepr::{Bytes, FromBytes};
+use casper_types::contracts::NamedKeys;
+use casper_types::{
+    runtime_args, ApiError, CLType, CLTyped, CLValue, ContractHash, EntryPoint, EntryPointAccess,
+    EntryPointType, EntryPoints, Key, Parameter, RuntimeArgs, U256, U512,
+};
+
+const CONTRACT_PACKAGE_NAME: &str = "policy_package_name";
+const CONTRACT_KEY: &str = "policy_hash";
+const CONTRACT_VERSION_KEY: &str = "policy_version";
+const KEY_TOKEN: &str = "token";
+const KEY_MIN_BALANCE: &str = "min_balance";
+const KEY_MAX_GAS: &str = "max_gas";
+const ENTRY_POINT_APPROVE_SPONSORSHIP: &str = "approve_sponsorship";
+const ENTRY_POINT_BALANCE_OF: &str = "balance_of";
+const ARG_TOKEN: &str = "token";
+const ARG_MIN_BALANCE: &str = "min_balance";
+const ARG_MAX_GAS: &str = "max_gas";
+const ARG_CALLER: &str = "caller";
+const ARG_ENTRY_POINT: &str = "entry_point";
+const ARG_ARGS: &str = "args";
+const ARG_GAS_AMOUNT: &str = "gas_amount";
+const ARG_ADDRESS: &str = "address";
+
+/// Reference sponsorship policy: approves calls whose `gas_amount` is within `max_gas` made by
+/// callers holding at least `min_balance` of `token`.
+///
+/// The relay calls it before moving any funds, within the relayer's deploy payment, so a policy
+/// should only read state and return quickly. Returning `false` makes the relayed call revert.
+#[no_mangle]
+pub extern "C" fn approve_sponsorship() {
+    let caller: Key = runtime::get_named_arg(ARG_CALLER);
+    let _entry_point: String = runtime::get_named_arg(ARG_ENTRY_POINT);
+    // Serialized `RuntimeArgs` of the relayed call, for policies looking at call arguments
+    let _args: Bytes = runtime::get_named_arg(ARG_ARGS);
+    let gas_amount: U512 = runtime::get_named_arg(ARG_GAS_AMOUNT);
+
+    let max_gas: U512 = read(KEY_MAX_GAS);
+    if !max_gas.is_zero() && gas_amount > max_gas {
+        ret(false)
+    }
+
+    if let Some(token) = read::<Option<ContractHash>>(KEY_TOKEN) {
+        let balance: U256 = runtime::call_contract(
+            token,
+            ENTRY_POINT_BALANCE_OF,
+            runtime_args! {
+                ARG_ADDRESS => caller,
+            },
+        );
+        if balance < read::<U256>(KEY_MIN_BALANCE) {
+            ret(false)
+        }
+    }
+    ret(true)
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let token: Option<ContractHash> = runtime::get_named_arg(ARG_TOKEN);
+    let min_balance: U256 = runtime::get_named_arg(ARG_MIN_BALANCE);
+    let max_gas: U512 = runtime::get_named_arg(ARG_MAX_GAS);
+
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(KEY_TOKEN.to_string(), storage::new_uref(token).into());
+    named_keys.insert(
+        KEY_MIN_BALANCE.to_string(),
+        storage::new_uref(min_balance).into(),
+    );
+    named_keys.insert(KEY_MAX_GAS.to_string(), storage::new_uref(max_gas).into());
+
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_APPROVE_SPONSORSHIP,
+        vec![
+            Parameter::new(ARG_CALLER, CLType::Key),
+            Parameter::new(ARG_ENTRY_POINT, CLType::String),
+            Parameter::new(ARG_ARGS, Bytes::cl_type()),
+            Parameter::new(ARG_GAS_AMOUNT, CLType::U512),
+        ],
+        CLType::Bool,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    let (contract_hash, contract_version) = storage::new_contract(
+        entry_points,
+        Some(named_keys),
+        Some(CONTRACT_PACKAGE_NAME.to_string()),
+        None,
+    );
+
+    runtime::put_key(
+        CONTRACT_VERSION_KEY,
+        storage::new_uref(contract_version).into(),
+    );
+    runtime::put_key(CONTRACT_KEY, contract_hash.into());
+}
+
+fn read<T: CLTyped + FromBytes>(name: &str) -> T {
+    let uref = runtime::get_key(name)
+        .unwrap_or_revert_with(ApiError::MissingKey)
+        .into_uref()
+        .unwrap_or_revert();
+    storage::read(uref).unwrap_or_revert().unwrap_or_revert()
+}
+
+fn ret(approved: bool) -> ! {
+    runtime::ret(CLValue::from_t(approved).unwrap_or_revert())
+}
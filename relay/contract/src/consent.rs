@@ -0,0 +1,78 @@
+use crate::errors::Error;
+use crate::{constants, utils};
+use alloc::string::String;
+use alloc::vec::Vec;
+use casper_contract::contract_api::runtime;
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+use casper_types::{ContractHash, Key, U512};
+
+/// Entry points the caller consents to (all of them when empty), expiry and remaining uses.
+type Consent = (Vec<String>, Option<u64>, Option<u64>);
+
+fn consent_key(caller: Key, contract_hash: ContractHash) -> String {
+    utils::get_dictionary_key(&(caller, contract_hash))
+}
+
+pub(crate) fn set(
+    caller: Key,
+    contract_hash: ContractHash,
+    entry_points: Vec<String>,
+    expiry: Option<u64>,
+    max_uses: Option<u64>,
+    max_spend: Option<U512>,
+) {
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_CONSENT),
+        &consent_key(caller, contract_hash),
+        Some((entry_points, expiry, max_uses)),
+    );
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_CONSENT_SPEND),
+        &consent_key(caller, contract_hash),
+        max_spend,
+    );
+}
+
+pub(crate) fn revoke(caller: Key, contract_hash: ContractHash) {
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_CONSENT),
+        &consent_key(caller, contract_hash),
+        None::<Consent>,
+    );
+}
+
+/// Reverts unless the caller consented to relayed calls of the entry point, and counts this call
+/// against the consent's uses.
+pub(crate) fn use_consent(caller: Key, contract_hash: ContractHash, entry_point: &str) {
+    let consents = utils::get_uref(constants::KEY_CONSENT);
+    let key = consent_key(caller, contract_hash);
+    let (entry_points, expiry, uses) = utils::get_storage_dic::<Option<Consent>>(consents, &key)
+        .flatten()
+        .unwrap_or_revert_with(Error::ConsentMissing);
+
+    let now: u64 = runtime::get_blocktime().into();
+    let expired = matches!(expiry, Some(expiry) if now >= expiry);
+    let covered = entry_points.is_empty() || entry_points.iter().any(|name| name == entry_point);
+    if expired || !covered || uses == Some(0) {
+        runtime::revert(Error::ConsentMissing)
+    }
+
+    if let Some(uses) = uses {
+        utils::write_storage_dic(consents, &key, Some((entry_points, expiry, Some(uses - 1))));
+    }
+}
+
+/// Counts `amount` charged to the caller against what the consent lets relayers spend, reverting
+/// with `SpendLimitExceeded` past it. Consents without a spend limit allow any amount.
+pub(crate) fn use_spend(caller: Key, contract_hash: ContractHash, amount: U512) {
+    let spends = utils::get_uref(constants::KEY_CONSENT_SPEND);
+    let key = consent_key(caller, contract_hash);
+    let remaining = match utils::get_storage_dic::<Option<U512>>(spends, &key).flatten() {
+        Some(remaining) => remaining,
+        None => return,
+    };
+    if amount > remaining {
+        runtime::revert(Error::SpendLimitExceeded)
+    }
+    utils::write_storage_dic(spends, &key, Some(remaining - amount));
+}
@@ -11,6 +11,47 @@ pub const KEY_FEE_PURSE: &str = "fee_purse";
 pub const KEY_OWNER_BALANCE: &str = "owner_balance";
 pub const KEY_NAME: &str = "name";
 pub const KEY_FEE_RATE: &str = "fee_rate";
+pub const KEY_CALLER_BALANCE: &str = "caller_balance";
+pub const KEY_SUBSIDY: &str = "subsidy";
+pub const KEY_SUBSIDY_USAGE: &str = "subsidy_usage";
+pub const KEY_SWAP_ROUTER: &str = "swap_router";
+pub const KEY_PAYOUT: &str = "payout";
+pub const KEY_PAYOUT_BALANCE: &str = "payout_balance";
+pub const KEY_PAYOUT_PURSE: &str = "payout_purse";
+pub const KEY_LOW_BALANCE: &str = "low_balance";
+pub const KEY_TOP_UP: &str = "top_up";
+pub const KEY_CREDIT: &str = "credit";
+pub const KEY_CREDIT_PURSE: &str = "credit_purse";
+pub const KEY_ENTRY_POINT_RESTRICTED: &str = "entry_point_restricted";
+pub const KEY_ENTRY_POINT_GAS_CAP: &str = "entry_point_gas_cap";
+pub const KEY_CALLER_LIST_MODE: &str = "caller_list_mode";
+pub const KEY_CALLER_ALLOWLIST: &str = "caller_allowlist";
+pub const KEY_CALLER_DENYLIST: &str = "caller_denylist";
+pub const KEY_QUOTA: &str = "quota";
+pub const KEY_QUOTA_USAGE: &str = "quota_usage";
+pub const KEY_BUDGET: &str = "budget";
+pub const KEY_BUDGET_USAGE: &str = "budget_usage";
+pub const KEY_POLICY: &str = "policy";
+pub const KEY_POST_HOOK: &str = "post_hook";
+pub const KEY_SUSPENDED: &str = "suspended";
+pub const KEY_DENYLIST: &str = "denylist";
+pub const KEY_CONSENT: &str = "consent";
+pub const KEY_CONSENT_SPEND: &str = "consent_spend";
+pub const KEY_PACKAGE_VERSION: &str = "package_version";
+pub const KEY_INJECTED_ARGS: &str = "injected_args";
+pub const KEY_NONCE: &str = "nonce";
+pub const KEY_CONTEXT: &str = "context";
+pub const KEY_ESCROW: &str = "escrow";
+pub const KEY_ESCROW_TIMEOUT: &str = "escrow_timeout";
+pub const KEY_RESERVATION: &str = "reservation";
+pub const KEY_RESERVED_CALL: &str = "reserved_call";
+pub const KEY_RESERVATION_TIMEOUT: &str = "reservation_timeout";
+/// Reservations expire after an hour unless the installer changes it.
+pub const DEFAULT_RESERVATION_TIMEOUT: u64 = 3_600_000;
+pub const KEY_RELAYERS: &str = "relayers";
+pub const KEY_OWNER_RELAYERS: &str = "owner_relayers";
+pub const KEY_OWNER_RELAYER_RESTRICTED: &str = "owner_relayer_restricted";
+pub const PREFIX_PAYOUT_PURSE: &str = "payout_purse_";
 
 pub const ARG_NAME: &str = "name";
 pub const ARG_ENTRY_POINT: &str = "entry_point";
@@ -23,15 +64,137 @@ pub const ARG_CEP18_HASH: &str = "cep18_hash";
 pub const ARG_AMOUNT: &str = "amount";
 pub const ARG_FEE_RATE: &str = "fee_rate";
 pub const ARG_RECIPIENT: &str = "recipient";
+pub const ARG_PURSE: &str = "purse";
 pub const ARG_SPENDER: &str = "spender";
+pub const ARG_ALLOWANCE: &str = "allowance";
+pub const ARG_PERIOD: &str = "period";
+pub const ARG_ROUTER: &str = "router";
+pub const ARG_AMOUNT_IN: &str = "amount_in";
+pub const ARG_AMOUNT_OUT_MIN: &str = "amount_out_min";
+pub const ARG_PATH: &str = "path";
+pub const ARG_TO: &str = "to";
+pub const ARG_DEADLINE: &str = "deadline";
+pub const ARG_TO_FEE_PURSE: &str = "to_fee_purse";
+pub const ARG_DESTINATION: &str = "destination";
+pub const ARG_ACCUMULATE: &str = "accumulate";
+pub const ARG_THRESHOLD: &str = "threshold";
+pub const ARG_EXPIRY: &str = "expiry";
+pub const ARG_RESTRICTED: &str = "restricted";
+pub const ARG_ENTRY_POINTS: &str = "entry_points";
+pub const ARG_MODE: &str = "mode";
+pub const ARG_DENYLIST: &str = "denylist";
+pub const ARG_ADD: &str = "add";
+pub const ARG_REMOVE: &str = "remove";
+pub const ARG_WINDOW: &str = "window";
+pub const ARG_MAX_CALLS: &str = "max_calls";
+pub const ARG_MAX_GAS: &str = "max_gas";
+pub const ARG_POLICY: &str = "policy";
+pub const ARG_ENABLED: &str = "enabled";
+pub const ARG_HOOK: &str = "hook";
+pub const ARG_FEE: &str = "fee";
+pub const ARG_CALLS: &str = "calls";
+pub const ARG_CONTRACT_PACKAGE: &str = "contract_package";
+pub const ARG_VERSION: &str = "version";
+pub const ARG_FIELDS: &str = "fields";
+pub const ARG_MAX_USES: &str = "max_uses";
+pub const ARG_MAX_SPEND: &str = "max_spend";
+pub const ARG_TIMEOUT: &str = "timeout";
+pub const ARG_REQUEST_ID: &str = "request_id";
+pub const ARG_GAS_USED: &str = "gas_used";
 
 pub const ARG_ARGS: &str = "args";
 pub const ENTRY_POINT_INIT: &str = "init";
+pub const ENTRY_POINT_MIGRATE: &str = "migrate";
 pub const ENTRY_POINT_GET_PURSE: &str = "get_purse";
 pub const ENTRY_POINT_DEPOSIT: &str = "deposit";
 pub const ENTRY_POINT_CALL_ON_BEHALF: &str = "call_on_behalf";
+pub const ENTRY_POINT_CALL_ON_BEHALF_BATCH: &str = "call_on_behalf_batch";
 pub const ENTRY_POINT_REGISTER: &str = "register";
 pub const ENTRY_POINT_SET_FEE_RATE: &str = "set_fee_rate";
 pub const ENTRY_POINT_CLAIM_FEE: &str = "claim_fee";
+pub const ENTRY_POINT_DEPOSIT_WALLET: &str = "deposit_wallet";
+pub const ENTRY_POINT_WITHDRAW_WALLET: &str = "withdraw_wallet";
+pub const ENTRY_POINT_SET_SUBSIDY: &str = "set_subsidy";
+pub const ENTRY_POINT_SET_ENTRY_POINTS: &str = "set_entry_points";
+pub const ENTRY_POINT_SET_CALLER_LIST_MODE: &str = "set_caller_list_mode";
+pub const ENTRY_POINT_UPDATE_CALLER_LIST: &str = "update_caller_list";
+pub const ENTRY_POINT_SET_QUOTA: &str = "set_quota";
+pub const ENTRY_POINT_SET_BUDGET: &str = "set_budget";
+pub const ENTRY_POINT_GET_BUDGET: &str = "get_budget";
+pub const ENTRY_POINT_SET_POLICY: &str = "set_policy";
+pub const ENTRY_POINT_APPROVE_SPONSORSHIP: &str = "approve_sponsorship";
+pub const ENTRY_POINT_REGISTER_PACKAGE: &str = "register_package";
+pub const ENTRY_POINT_SET_PACKAGE_VERSION: &str = "set_package_version";
+pub const ENTRY_POINT_CURRENT_CONTEXT: &str = "current_context";
+pub const ENTRY_POINT_SET_INJECTED_ARGS: &str = "set_injected_args";
+pub const ENTRY_POINT_SET_CONSENT: &str = "set_consent";
+pub const ENTRY_POINT_REVOKE_CONSENT: &str = "revoke_consent";
+pub const ENTRY_POINT_SET_ESCROW: &str = "set_escrow";
+pub const ENTRY_POINT_CONFIRM_PAYMENT: &str = "confirm_payment";
+pub const ENTRY_POINT_REFUND_PAYMENT: &str = "refund_payment";
+pub const ENTRY_POINT_RECLAIM_PAYMENT: &str = "reclaim_payment";
+pub const ENTRY_POINT_RESERVE: &str = "reserve";
+pub const ENTRY_POINT_CALL_RESERVED: &str = "call_reserved";
+pub const ENTRY_POINT_SETTLE: &str = "settle";
+pub const ENTRY_POINT_EXPIRE_RESERVATION: &str = "expire_reservation";
+pub const ENTRY_POINT_SET_RESERVATION_TIMEOUT: &str = "set_reservation_timeout";
+pub const ENTRY_POINT_UPDATE_RELAYERS: &str = "update_relayers";
+pub const ENTRY_POINT_SET_OWNER_RELAYERS: &str = "set_owner_relayers";
+pub const ENTRY_POINT_UPDATE_DENIED_CALLERS: &str = "update_denied_callers";
+pub const ENTRY_POINT_UPDATE_DENIED_CONTRACTS: &str = "update_denied_contracts";
+pub const ENTRY_POINT_UPDATE_DENIED_PACKAGES: &str = "update_denied_packages";
+pub const ENTRY_POINT_SUSPEND_SPONSORSHIP: &str = "suspend_sponsorship";
+pub const ENTRY_POINT_RESUME_SPONSORSHIP: &str = "resume_sponsorship";
+pub const ENTRY_POINT_IS_SUSPENDED: &str = "is_suspended";
+pub const ENTRY_POINT_SET_POST_HOOK: &str = "set_post_hook";
+pub const ENTRY_POINT_POST_RELAY: &str = "post_relay";
+pub const ENTRY_POINT_GRANT_CREDIT: &str = "grant_credit";
+pub const ENTRY_POINT_SET_LOW_BALANCE: &str = "set_low_balance";
+pub const ENTRY_POINT_TOP_UP: &str = "top_up";
 pub const ENTRY_POINT_ALLOWANCE: &str = "allowance";
 pub const ENTRY_POINT_TRANSFER_FROM: &str = "transfer_from";
+pub const ENTRY_POINT_APPROVE: &str = "approve";
+pub const ENTRY_POINT_TRANSFER: &str = "transfer";
+pub const ENTRY_POINT_SET_PAYOUT: &str = "set_payout";
+pub const ENTRY_POINT_CLAIM_PAYOUT: &str = "claim_payout";
+pub const ENTRY_POINT_SET_SWAP_ROUTER: &str = "set_swap_router";
+pub const ENTRY_POINT_SWAP_TOKENS: &str = "swap_tokens";
+pub const ENTRY_POINT_SWAP_EXACT_TOKENS_FOR_CSPR: &str = "swap_exact_tokens_for_cspr";
+
+/// Dictionaries of the relay, created at install and by `migrate` after an upgrade.
+pub const DICTIONARIES: &[&str] = &[
+    KEY_REGISTERED_CONTRACT,
+    KEY_OWNER_BALANCE,
+    KEY_CALLER_BALANCE,
+    KEY_SUBSIDY,
+    KEY_SUBSIDY_USAGE,
+    KEY_PAYOUT,
+    KEY_PAYOUT_BALANCE,
+    KEY_LOW_BALANCE,
+    KEY_TOP_UP,
+    KEY_CREDIT,
+    KEY_ENTRY_POINT_RESTRICTED,
+    KEY_ENTRY_POINT_GAS_CAP,
+    KEY_CALLER_LIST_MODE,
+    KEY_CALLER_ALLOWLIST,
+    KEY_CALLER_DENYLIST,
+    KEY_QUOTA,
+    KEY_QUOTA_USAGE,
+    KEY_BUDGET,
+    KEY_BUDGET_USAGE,
+    KEY_POLICY,
+    KEY_POST_HOOK,
+    KEY_SUSPENDED,
+    KEY_DENYLIST,
+    KEY_CONSENT,
+    KEY_CONSENT_SPEND,
+    KEY_ESCROW,
+    KEY_ESCROW_TIMEOUT,
+    KEY_RESERVATION,
+    KEY_RESERVED_CALL,
+    KEY_PACKAGE_VERSION,
+    KEY_INJECTED_ARGS,
+    KEY_RELAYERS,
+    KEY_OWNER_RELAYERS,
+    KEY_OWNER_RELAYER_RESTRICTED,
+];
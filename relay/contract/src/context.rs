@@ -0,0 +1,119 @@
+use crate::{constants, utils};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use casper_contract::contract_api::runtime;
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+use casper_types::account::AccountHash;
+use casper_types::{ApiError, CLValue, ContractHash, Key, RuntimeArgs};
+use core::convert::TryFrom;
+
+/// Relay context fields a registered contract can have injected into its relayed calls.
+#[repr(u8)]
+pub enum ContextField {
+    Caller = 0,
+    Owner = 1,
+    Relayer = 2,
+    Nonce = 3,
+    RelayPackage = 4,
+}
+
+impl TryFrom<u8> for ContextField {
+    type Error = ApiError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ContextField::Caller),
+            1 => Ok(ContextField::Owner),
+            2 => Ok(ContextField::Relayer),
+            3 => Ok(ContextField::Nonce),
+            4 => Ok(ContextField::RelayPackage),
+            _ => Err(ApiError::InvalidArgument),
+        }
+    }
+}
+
+/// Context of one relayed call.
+pub(crate) struct Context {
+    pub caller: Key,
+    pub owner: Key,
+    pub relayer: AccountHash,
+    pub nonce: u64,
+}
+
+/// Context as stored while a relayed call is in flight: caller, owner, (relayer, nonce).
+pub(crate) type StoredContext = (Key, Key, (AccountHash, u64));
+
+pub(crate) fn current() -> Option<StoredContext> {
+    utils::get_storage::<Option<StoredContext>>(constants::KEY_CONTEXT)
+}
+
+/// Makes `context` the active one for the duration of a relayed call and returns the previous one,
+/// to be restored with `exit` once the call returns.
+pub(crate) fn enter(context: &Context) -> Option<StoredContext> {
+    let previous = current();
+    utils::write_storage(
+        constants::KEY_CONTEXT,
+        Some((
+            context.caller,
+            context.owner,
+            (context.relayer, context.nonce),
+        )),
+    );
+    previous
+}
+
+pub(crate) fn exit(previous: Option<StoredContext>) {
+    utils::write_storage(constants::KEY_CONTEXT, previous);
+}
+
+/// Returns the relay-wide nonce of a new relayed call.
+pub(crate) fn next_nonce() -> u64 {
+    let nonce = utils::get_storage::<u64>(constants::KEY_NONCE);
+    utils::write_storage(constants::KEY_NONCE, nonce + 1);
+    nonce
+}
+
+/// Sets which context fields are injected into the contract's relayed calls, and under which
+/// argument names.
+pub(crate) fn set_injected_args(contract_hash: ContractHash, fields: Vec<(u8, String)>) {
+    for (field, _) in fields.iter() {
+        ContextField::try_from(*field).unwrap_or_revert();
+    }
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_INJECTED_ARGS),
+        contract_hash.to_string().as_str(),
+        fields,
+    );
+}
+
+/// Injects the configured context fields into the relayed call's arguments, by default only the
+/// caller as `caller`. Arguments the relayer already set under those names are rejected.
+pub(crate) fn inject(contract_hash: ContractHash, args: &mut RuntimeArgs, context: &Context) {
+    let fields = utils::get_storage_dic::<Vec<(u8, String)>>(
+        utils::get_uref(constants::KEY_INJECTED_ARGS),
+        contract_hash.to_string().as_str(),
+    )
+    .unwrap_or_else(|| {
+        vec![(
+            ContextField::Caller as u8,
+            constants::ARG_CALLER.to_string(),
+        )]
+    });
+
+    for (field, name) in fields {
+        if args.get(&name).is_some() {
+            runtime::revert(ApiError::InvalidArgument)
+        }
+        let value = match ContextField::try_from(field).unwrap_or_revert() {
+            ContextField::Caller => CLValue::from_t(context.caller),
+            ContextField::Owner => CLValue::from_t(context.owner),
+            ContextField::Relayer => CLValue::from_t(context.relayer),
+            ContextField::Nonce => CLValue::from_t(context.nonce),
+            ContextField::RelayPackage => {
+                CLValue::from_t(utils::get_contract_package().unwrap_or_revert())
+            }
+        };
+        args.insert_cl_value(name, value.unwrap_or_revert());
+    }
+}
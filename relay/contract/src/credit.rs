@@ -0,0 +1,79 @@
+use crate::events::{CreditExpired, UseCredit};
+use crate::{constants, utils};
+use casper_contract::contract_api::{runtime, system};
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+use casper_types::{Key, U512};
+
+/// Reads the owner's promotional credit, returning an expired one to the fee purse first.
+fn get_credit(owner: Key) -> (U512, Option<u64>) {
+    let credit = utils::get_storage_dic::<(U512, Option<u64>)>(
+        utils::get_uref(constants::KEY_CREDIT),
+        &utils::key_to_str(&owner),
+    )
+    .unwrap_or_default();
+    let (amount, expiry) = credit;
+    let now: u64 = runtime::get_blocktime().into();
+    match expiry {
+        Some(expiry) if !amount.is_zero() && now >= expiry => {
+            system::transfer_from_purse_to_purse(
+                utils::get_uref(constants::KEY_CREDIT_PURSE),
+                utils::get_uref(constants::KEY_FEE_PURSE),
+                amount,
+                None,
+            )
+            .unwrap_or_revert();
+            utils::write_storage_dic(
+                utils::get_uref(constants::KEY_CREDIT),
+                &utils::key_to_str(&owner),
+                (U512::zero(), None::<u64>),
+            );
+            casper_event_standard::emit(CreditExpired::new(owner, amount));
+            (U512::zero(), None)
+        }
+        _ => credit,
+    }
+}
+
+/// Adds `amount` of credit funded from the fee purse. The latest grant's expiry applies to the
+/// owner's whole credit.
+pub(crate) fn grant(owner: Key, amount: U512, expiry: Option<u64>) {
+    let (credit, _) = get_credit(owner);
+    system::transfer_from_purse_to_purse(
+        utils::get_uref(constants::KEY_FEE_PURSE),
+        utils::get_uref(constants::KEY_CREDIT_PURSE),
+        amount,
+        None,
+    )
+    .unwrap_or_revert();
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_CREDIT),
+        &utils::key_to_str(&owner),
+        (credit + amount, expiry),
+    );
+}
+
+/// Spends the owner's credit towards `cost` by moving it into the deposit purse, and returns the
+/// amount spent.
+pub(crate) fn spend(owner: Key, cost: U512) -> U512 {
+    let (credit, expiry) = get_credit(owner);
+    let amount = credit.min(cost);
+    if amount.is_zero() {
+        return amount;
+    }
+
+    system::transfer_from_purse_to_purse(
+        utils::get_uref(constants::KEY_CREDIT_PURSE),
+        utils::get_uref(constants::KEY_DEPOSIT_PURSE),
+        amount,
+        None,
+    )
+    .unwrap_or_revert();
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_CREDIT),
+        &utils::key_to_str(&owner),
+        (credit - amount, expiry),
+    );
+
+    casper_event_standard::emit(UseCredit::new(owner, amount));
+    amount
+}
@@ -7,6 +7,26 @@ pub enum Error {
     Unregistered = 2,
     InsufficientBalance = 3,
     InsufficientAmount = 4,
+    SwapRouterNotSet = 5,
+    SlippageExceeded = 6,
+    EntryPointNotAllowed = 7,
+    GasCapExceeded = 8,
+    CallerNotAllowed = 9,
+    QuotaExceeded = 10,
+    BudgetExceeded = 11,
+    SponsorshipDenied = 12,
+    SponsorshipSuspended = 13,
+    Denied = 14,
+    RelayerNotAllowed = 15,
+    ConsentMissing = 16,
+    EscrowNotFound = 17,
+    EscrowLocked = 18,
+    ReservationNotFound = 19,
+    ReservationExpired = 20,
+    ReservationActive = 21,
+    ReservationNotAllowed = 22,
+    BatchOwnerMismatch = 23,
+    SpendLimitExceeded = 24,
 }
 
 impl From<Error> for ApiError {
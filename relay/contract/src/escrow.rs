@@ -0,0 +1,65 @@
+use crate::errors::Error;
+use crate::{constants, utils};
+use alloc::string::ToString;
+use casper_contract::contract_api::runtime;
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+use casper_types::{ContractHash, Key, U512};
+
+/// Target contract, caller, and the amount held with the block time the caller may reclaim it at.
+pub(crate) type Escrow = (ContractHash, Key, (U512, u64));
+
+/// Returns how long payments to the contract stay in escrow, `None` when they are paid directly.
+pub(crate) fn get_timeout(contract_hash: ContractHash) -> Option<u64> {
+    utils::get_storage_dic::<Option<u64>>(
+        utils::get_uref(constants::KEY_ESCROW_TIMEOUT),
+        contract_hash.to_string().as_str(),
+    )
+    .flatten()
+}
+
+pub(crate) fn set_timeout(contract_hash: ContractHash, timeout: Option<u64>) {
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_ESCROW_TIMEOUT),
+        contract_hash.to_string().as_str(),
+        timeout,
+    );
+}
+
+/// Holds a payment already taken from the caller's wallet until the target settles it, and returns
+/// the block time from which the caller may reclaim it.
+pub(crate) fn hold(
+    request_id: u64,
+    contract_hash: ContractHash,
+    caller: Key,
+    amount: U512,
+    timeout: u64,
+) -> u64 {
+    let now: u64 = runtime::get_blocktime().into();
+    let deadline = now + timeout;
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_ESCROW),
+        request_id.to_string().as_str(),
+        Some((contract_hash, caller, (amount, deadline))),
+    );
+    deadline
+}
+
+pub(crate) fn get(request_id: u64) -> Escrow {
+    utils::get_storage_dic::<Option<Escrow>>(
+        utils::get_uref(constants::KEY_ESCROW),
+        request_id.to_string().as_str(),
+    )
+    .flatten()
+    .unwrap_or_revert_with(Error::EscrowNotFound)
+}
+
+/// Removes a settled payment so it can't be released twice.
+pub(crate) fn take(request_id: u64) -> Escrow {
+    let escrow = get(request_id);
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_ESCROW),
+        request_id.to_string().as_str(),
+        None::<Escrow>,
+    );
+    escrow
+}
@@ -1,7 +1,8 @@
 use alloc::string::{String, ToString};
+use alloc::vec::Vec;
 use casper_event_standard::Event;
 use casper_types::account::AccountHash;
-use casper_types::{ContractHash, U512};
+use casper_types::{ContractHash, ContractPackageHash, Key, U256, U512};
 
 #[derive(Event)]
 pub struct Register {
@@ -22,11 +23,302 @@ pub struct CallOnBehalf {
     caller: String,
     entry_point: String,
     gas_amount: String,
+    owner_amount: String,
+    caller_amount: String,
     cep18_hash: Option<String>,
+    result_hash: String,
+}
+
+#[derive(Event)]
+pub struct RegisterPackage {
+    contract_package_hash: String,
+    owner: String,
+}
+
+#[derive(Event)]
+pub struct SetPackageVersion {
+    contract_package_hash: String,
+    version: Option<u32>,
+}
+
+#[derive(Event)]
+pub struct SetInjectedArgs {
+    contract_hash: String,
+    fields: Vec<(u8, String)>,
+}
+
+#[derive(Event)]
+pub struct SetSubsidy {
+    contract_hash: String,
+    allowance: Option<String>,
+    period: u64,
+}
+
+#[derive(Event)]
+pub struct SetPayout {
+    relayer: String,
+    destination: Option<String>,
+    accumulate: bool,
+}
+
+#[derive(Event)]
+pub struct Payout {
+    relayer: String,
+    recipient: Option<String>,
+    amount: String,
+    cep18_hash: Option<String>,
+}
+
+#[derive(Event)]
+pub struct ClaimPayout {
+    relayer: String,
+    recipient: String,
+    amount: String,
+    cep18_hash: Option<String>,
+}
+
+#[derive(Event)]
+pub struct WalletDeposit {
+    account: String,
+    amount: String,
+}
+
+#[derive(Event)]
+pub struct SetLowBalance {
+    owner: String,
+    threshold: String,
+    top_up: bool,
+}
+
+#[derive(Event)]
+pub struct LowBalance {
+    owner: String,
+    balance: String,
+    threshold: String,
+}
+
+#[derive(Event)]
+pub struct TopUp {
+    owner: String,
+    cep18_hash: String,
+    amount_in: String,
+    amount_out: String,
+}
+
+#[derive(Event)]
+pub struct SetEntryPoints {
+    contract_hash: String,
+    restricted: bool,
+    entry_points: Vec<String>,
+}
+
+#[derive(Event)]
+pub struct SetCallerListMode {
+    contract_hash: String,
+    mode: u8,
+}
+
+#[derive(Event)]
+pub struct UpdateCallerList {
+    contract_hash: String,
+    denylist: bool,
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+#[derive(Event)]
+pub struct SetConsent {
+    caller: String,
+    contract_hash: String,
+    entry_points: Vec<String>,
+    expiry: Option<u64>,
+    max_uses: Option<u64>,
+    max_spend: Option<String>,
+}
+
+#[derive(Event)]
+pub struct RevokeConsent {
+    caller: String,
+    contract_hash: String,
+}
+
+#[derive(Event)]
+pub struct UpdateRelayers {
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+#[derive(Event)]
+pub struct SetOwnerRelayers {
+    owner: String,
+    restricted: bool,
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+#[derive(Event)]
+pub struct UpdateDeniedCallers {
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+#[derive(Event)]
+pub struct UpdateDeniedContracts {
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+#[derive(Event)]
+pub struct UpdateDeniedPackages {
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+#[derive(Event)]
+pub struct SetQuota {
+    contract_hash: String,
+    window: u64,
+    max_calls: u64,
+    max_gas: String,
+}
+
+#[derive(Event)]
+pub struct SetBudget {
+    contract_hash: String,
+    amount: Option<String>,
+    period: u64,
+}
+
+#[derive(Event)]
+pub struct SetPolicy {
+    contract_hash: String,
+    policy: Option<String>,
+}
+
+#[derive(Event)]
+pub struct SetPostHook {
+    contract_hash: String,
+    enabled: bool,
+    hook: Option<String>,
+}
+
+#[derive(Event)]
+pub struct SuspendSponsorship {
+    owner: String,
+    contract_hash: Option<String>,
+}
+
+#[derive(Event)]
+pub struct ResumeSponsorship {
+    owner: String,
+    contract_hash: Option<String>,
+}
+
+#[derive(Event)]
+pub struct GrantCredit {
+    owner: String,
+    amount: String,
+    expiry: Option<u64>,
+}
+
+#[derive(Event)]
+pub struct UseCredit {
+    owner: String,
+    amount: String,
+}
+
+#[derive(Event)]
+pub struct CreditExpired {
+    owner: String,
+    amount: String,
+}
+
+#[derive(Event)]
+pub struct Swap {
+    relayer: String,
+    cep18_hash: String,
+    amount_in: String,
+    amount_out: String,
+    to_fee_purse: bool,
+}
+
+#[derive(Event)]
+pub struct WalletWithdraw {
+    account: String,
+    amount: String,
+}
+
+#[derive(Event)]
+pub struct Payment {
+    contract_hash: String,
+    caller: String,
+    amount: String,
+}
+
+#[derive(Event)]
+pub struct Reserve {
+    request_id: u64,
+    contract_hash: String,
+    owner: String,
+    relayer: String,
+    amount: String,
+    deadline: u64,
+}
+
+#[derive(Event)]
+pub struct Settle {
+    request_id: u64,
+    relayer: String,
+    gas_amount: String,
+    fee: String,
+    refund: String,
+}
+
+#[derive(Event)]
+pub struct ExpireReservation {
+    request_id: u64,
+    owner: String,
+    amount: String,
+}
+
+#[derive(Event)]
+pub struct SetEscrow {
+    contract_hash: String,
+    timeout: Option<u64>,
+}
+
+#[derive(Event)]
+pub struct EscrowPayment {
+    request_id: u64,
+    contract_hash: String,
+    caller: String,
+    amount: String,
+    deadline: u64,
+}
+
+#[derive(Event)]
+pub struct ConfirmPayment {
+    request_id: u64,
+    contract_hash: String,
+    amount: String,
+}
+
+#[derive(Event)]
+pub struct RefundPayment {
+    request_id: u64,
+    caller: String,
+    amount: String,
+}
+
+#[derive(Event)]
+pub struct ReclaimPayment {
+    request_id: u64,
+    caller: String,
+    amount: String,
 }
 
 impl Register {
-    pub fn new(contract_hash: ContractHash, owner: AccountHash) -> Self {
+    pub fn new(contract_hash: ContractHash, owner: Key) -> Self {
         Register {
             contract_hash: contract_hash.to_formatted_string(),
             owner: owner.to_formatted_string(),
@@ -35,7 +327,7 @@ impl Register {
 }
 
 impl Deposit {
-    pub fn new(owner: AccountHash, amount: U512) -> Self {
+    pub fn new(owner: Key, amount: U512) -> Self {
         Deposit {
             owner: owner.to_formatted_string(),
             amount: amount.to_string(),
@@ -44,13 +336,17 @@ impl Deposit {
 }
 
 impl CallOnBehalf {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         contract_hash: ContractHash,
-        owner: AccountHash,
-        caller: AccountHash,
+        owner: Key,
+        caller: Key,
         entry_point: String,
         gas_amount: U512,
+        owner_amount: U512,
+        caller_amount: U512,
         cep18_hash: Option<ContractHash>,
+        result_hash: String,
     ) -> Self {
         CallOnBehalf {
             contract_hash: contract_hash.to_formatted_string(),
@@ -58,6 +354,512 @@ impl CallOnBehalf {
             caller: caller.to_formatted_string(),
             entry_point,
             gas_amount: gas_amount.to_string(),
+            owner_amount: owner_amount.to_string(),
+            caller_amount: caller_amount.to_string(),
+            cep18_hash: cep18_hash.map(|hash| hash.to_formatted_string()),
+            result_hash,
+        }
+    }
+}
+
+impl RegisterPackage {
+    pub fn new(contract_package_hash: ContractPackageHash, owner: Key) -> Self {
+        RegisterPackage {
+            contract_package_hash: contract_package_hash.to_formatted_string(),
+            owner: owner.to_formatted_string(),
+        }
+    }
+}
+
+impl SetPackageVersion {
+    pub fn new(contract_package_hash: ContractPackageHash, version: Option<u32>) -> Self {
+        SetPackageVersion {
+            contract_package_hash: contract_package_hash.to_formatted_string(),
+            version,
+        }
+    }
+}
+
+impl SetInjectedArgs {
+    pub fn new(contract_hash: ContractHash, fields: Vec<(u8, String)>) -> Self {
+        SetInjectedArgs {
+            contract_hash: contract_hash.to_formatted_string(),
+            fields,
+        }
+    }
+}
+
+impl SetSubsidy {
+    pub fn new(contract_hash: ContractHash, allowance: Option<U512>, period: u64) -> Self {
+        SetSubsidy {
+            contract_hash: contract_hash.to_formatted_string(),
+            allowance: allowance.map(|allowance| allowance.to_string()),
+            period,
+        }
+    }
+}
+
+impl WalletDeposit {
+    pub fn new(account: Key, amount: U512) -> Self {
+        WalletDeposit {
+            account: account.to_formatted_string(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+impl WalletWithdraw {
+    pub fn new(account: Key, amount: U512) -> Self {
+        WalletWithdraw {
+            account: account.to_formatted_string(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+impl Payment {
+    pub fn new(contract_hash: ContractHash, caller: Key, amount: U512) -> Self {
+        Payment {
+            contract_hash: contract_hash.to_formatted_string(),
+            caller: caller.to_formatted_string(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+impl Reserve {
+    pub fn new(
+        request_id: u64,
+        contract_hash: ContractHash,
+        owner: Key,
+        relayer: AccountHash,
+        amount: U512,
+        deadline: u64,
+    ) -> Self {
+        Reserve {
+            request_id,
+            contract_hash: contract_hash.to_formatted_string(),
+            owner: owner.to_formatted_string(),
+            relayer: relayer.to_formatted_string(),
+            amount: amount.to_string(),
+            deadline,
+        }
+    }
+}
+
+impl Settle {
+    pub fn new(
+        request_id: u64,
+        relayer: AccountHash,
+        gas_amount: U512,
+        fee: U512,
+        refund: U512,
+    ) -> Self {
+        Settle {
+            request_id,
+            relayer: relayer.to_formatted_string(),
+            gas_amount: gas_amount.to_string(),
+            fee: fee.to_string(),
+            refund: refund.to_string(),
+        }
+    }
+}
+
+impl ExpireReservation {
+    pub fn new(request_id: u64, owner: Key, amount: U512) -> Self {
+        ExpireReservation {
+            request_id,
+            owner: owner.to_formatted_string(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+impl SetEscrow {
+    pub fn new(contract_hash: ContractHash, timeout: Option<u64>) -> Self {
+        SetEscrow {
+            contract_hash: contract_hash.to_formatted_string(),
+            timeout,
+        }
+    }
+}
+
+impl EscrowPayment {
+    pub fn new(
+        request_id: u64,
+        contract_hash: ContractHash,
+        caller: Key,
+        amount: U512,
+        deadline: u64,
+    ) -> Self {
+        EscrowPayment {
+            request_id,
+            contract_hash: contract_hash.to_formatted_string(),
+            caller: caller.to_formatted_string(),
+            amount: amount.to_string(),
+            deadline,
+        }
+    }
+}
+
+impl ConfirmPayment {
+    pub fn new(request_id: u64, contract_hash: ContractHash, amount: U512) -> Self {
+        ConfirmPayment {
+            request_id,
+            contract_hash: contract_hash.to_formatted_string(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+impl RefundPayment {
+    pub fn new(request_id: u64, caller: Key, amount: U512) -> Self {
+        RefundPayment {
+            request_id,
+            caller: caller.to_formatted_string(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+impl ReclaimPayment {
+    pub fn new(request_id: u64, caller: Key, amount: U512) -> Self {
+        ReclaimPayment {
+            request_id,
+            caller: caller.to_formatted_string(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+impl SetLowBalance {
+    pub fn new(owner: Key, threshold: U512, top_up: bool) -> Self {
+        SetLowBalance {
+            owner: owner.to_formatted_string(),
+            threshold: threshold.to_string(),
+            top_up,
+        }
+    }
+}
+
+impl LowBalance {
+    pub fn new(owner: Key, balance: U512, threshold: U512) -> Self {
+        LowBalance {
+            owner: owner.to_formatted_string(),
+            balance: balance.to_string(),
+            threshold: threshold.to_string(),
+        }
+    }
+}
+
+impl TopUp {
+    pub fn new(owner: Key, cep18_hash: ContractHash, amount_in: U256, amount_out: U512) -> Self {
+        TopUp {
+            owner: owner.to_formatted_string(),
+            cep18_hash: cep18_hash.to_formatted_string(),
+            amount_in: amount_in.to_string(),
+            amount_out: amount_out.to_string(),
+        }
+    }
+}
+
+impl SetEntryPoints {
+    pub fn new(contract_hash: ContractHash, restricted: bool, entry_points: Vec<String>) -> Self {
+        SetEntryPoints {
+            contract_hash: contract_hash.to_formatted_string(),
+            restricted,
+            entry_points,
+        }
+    }
+}
+
+impl SetCallerListMode {
+    pub fn new(contract_hash: ContractHash, mode: u8) -> Self {
+        SetCallerListMode {
+            contract_hash: contract_hash.to_formatted_string(),
+            mode,
+        }
+    }
+}
+
+impl UpdateCallerList {
+    pub fn new(
+        contract_hash: ContractHash,
+        denylist: bool,
+        added: &[Key],
+        removed: &[Key],
+    ) -> Self {
+        UpdateCallerList {
+            contract_hash: contract_hash.to_formatted_string(),
+            denylist,
+            added: added
+                .iter()
+                .map(|caller| caller.to_formatted_string())
+                .collect(),
+            removed: removed
+                .iter()
+                .map(|caller| caller.to_formatted_string())
+                .collect(),
+        }
+    }
+}
+
+impl SetConsent {
+    pub fn new(
+        caller: Key,
+        contract_hash: ContractHash,
+        entry_points: Vec<String>,
+        expiry: Option<u64>,
+        max_uses: Option<u64>,
+        max_spend: Option<U512>,
+    ) -> Self {
+        SetConsent {
+            caller: caller.to_formatted_string(),
+            contract_hash: contract_hash.to_formatted_string(),
+            entry_points,
+            expiry,
+            max_uses,
+            max_spend: max_spend.map(|max_spend| max_spend.to_string()),
+        }
+    }
+}
+
+impl RevokeConsent {
+    pub fn new(caller: Key, contract_hash: ContractHash) -> Self {
+        RevokeConsent {
+            caller: caller.to_formatted_string(),
+            contract_hash: contract_hash.to_formatted_string(),
+        }
+    }
+}
+
+impl UpdateRelayers {
+    pub fn new(added: &[AccountHash], removed: &[AccountHash]) -> Self {
+        UpdateRelayers {
+            added: added
+                .iter()
+                .map(|relayer| relayer.to_formatted_string())
+                .collect(),
+            removed: removed
+                .iter()
+                .map(|relayer| relayer.to_formatted_string())
+                .collect(),
+        }
+    }
+}
+
+impl SetOwnerRelayers {
+    pub fn new(
+        owner: Key,
+        restricted: bool,
+        added: &[AccountHash],
+        removed: &[AccountHash],
+    ) -> Self {
+        SetOwnerRelayers {
+            owner: owner.to_formatted_string(),
+            restricted,
+            added: added
+                .iter()
+                .map(|relayer| relayer.to_formatted_string())
+                .collect(),
+            removed: removed
+                .iter()
+                .map(|relayer| relayer.to_formatted_string())
+                .collect(),
+        }
+    }
+}
+
+impl UpdateDeniedCallers {
+    pub fn new(added: &[Key], removed: &[Key]) -> Self {
+        UpdateDeniedCallers {
+            added: added
+                .iter()
+                .map(|caller| caller.to_formatted_string())
+                .collect(),
+            removed: removed
+                .iter()
+                .map(|caller| caller.to_formatted_string())
+                .collect(),
+        }
+    }
+}
+
+impl UpdateDeniedContracts {
+    pub fn new(added: &[ContractHash], removed: &[ContractHash]) -> Self {
+        UpdateDeniedContracts {
+            added: added
+                .iter()
+                .map(|contract_hash| contract_hash.to_formatted_string())
+                .collect(),
+            removed: removed
+                .iter()
+                .map(|contract_hash| contract_hash.to_formatted_string())
+                .collect(),
+        }
+    }
+}
+
+impl UpdateDeniedPackages {
+    pub fn new(added: &[ContractPackageHash], removed: &[ContractPackageHash]) -> Self {
+        UpdateDeniedPackages {
+            added: added
+                .iter()
+                .map(|contract_package_hash| contract_package_hash.to_formatted_string())
+                .collect(),
+            removed: removed
+                .iter()
+                .map(|contract_package_hash| contract_package_hash.to_formatted_string())
+                .collect(),
+        }
+    }
+}
+
+impl SetQuota {
+    pub fn new(contract_hash: ContractHash, window: u64, max_calls: u64, max_gas: U512) -> Self {
+        SetQuota {
+            contract_hash: contract_hash.to_formatted_string(),
+            window,
+            max_calls,
+            max_gas: max_gas.to_string(),
+        }
+    }
+}
+
+impl SetBudget {
+    pub fn new(contract_hash: ContractHash, amount: Option<U512>, period: u64) -> Self {
+        SetBudget {
+            contract_hash: contract_hash.to_formatted_string(),
+            amount: amount.map(|amount| amount.to_string()),
+            period,
+        }
+    }
+}
+
+impl SetPolicy {
+    pub fn new(contract_hash: ContractHash, policy: Option<ContractPackageHash>) -> Self {
+        SetPolicy {
+            contract_hash: contract_hash.to_formatted_string(),
+            policy: policy.map(|policy| policy.to_formatted_string()),
+        }
+    }
+}
+
+impl SetPostHook {
+    pub fn new(
+        contract_hash: ContractHash,
+        enabled: bool,
+        hook: Option<ContractPackageHash>,
+    ) -> Self {
+        SetPostHook {
+            contract_hash: contract_hash.to_formatted_string(),
+            enabled,
+            hook: hook.map(|hook| hook.to_formatted_string()),
+        }
+    }
+}
+
+impl SuspendSponsorship {
+    pub fn new(owner: Key, contract_hash: Option<ContractHash>) -> Self {
+        SuspendSponsorship {
+            owner: owner.to_formatted_string(),
+            contract_hash: contract_hash.map(|contract_hash| contract_hash.to_formatted_string()),
+        }
+    }
+}
+
+impl ResumeSponsorship {
+    pub fn new(owner: Key, contract_hash: Option<ContractHash>) -> Self {
+        ResumeSponsorship {
+            owner: owner.to_formatted_string(),
+            contract_hash: contract_hash.map(|contract_hash| contract_hash.to_formatted_string()),
+        }
+    }
+}
+
+impl GrantCredit {
+    pub fn new(owner: Key, amount: U512, expiry: Option<u64>) -> Self {
+        GrantCredit {
+            owner: owner.to_formatted_string(),
+            amount: amount.to_string(),
+            expiry,
+        }
+    }
+}
+
+impl UseCredit {
+    pub fn new(owner: Key, amount: U512) -> Self {
+        UseCredit {
+            owner: owner.to_formatted_string(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+impl CreditExpired {
+    pub fn new(owner: Key, amount: U512) -> Self {
+        CreditExpired {
+            owner: owner.to_formatted_string(),
+            amount: amount.to_string(),
+        }
+    }
+}
+
+impl Swap {
+    pub fn new(
+        relayer: AccountHash,
+        cep18_hash: ContractHash,
+        amount_in: U256,
+        amount_out: U512,
+        to_fee_purse: bool,
+    ) -> Self {
+        Swap {
+            relayer: relayer.to_formatted_string(),
+            cep18_hash: cep18_hash.to_formatted_string(),
+            amount_in: amount_in.to_string(),
+            amount_out: amount_out.to_string(),
+            to_fee_purse,
+        }
+    }
+}
+
+impl SetPayout {
+    pub fn new(relayer: AccountHash, destination: Option<Key>, accumulate: bool) -> Self {
+        SetPayout {
+            relayer: relayer.to_formatted_string(),
+            destination: destination.map(|destination| destination.to_formatted_string()),
+            accumulate,
+        }
+    }
+}
+
+impl Payout {
+    pub fn new(
+        relayer: AccountHash,
+        recipient: Option<Key>,
+        amount: U512,
+        cep18_hash: Option<ContractHash>,
+    ) -> Self {
+        Payout {
+            relayer: relayer.to_formatted_string(),
+            recipient: recipient.map(|recipient| recipient.to_formatted_string()),
+            amount: amount.to_string(),
+            cep18_hash: cep18_hash.map(|hash| hash.to_formatted_string()),
+        }
+    }
+}
+
+impl ClaimPayout {
+    pub fn new(
+        relayer: AccountHash,
+        recipient: Key,
+        amount: U512,
+        cep18_hash: Option<ContractHash>,
+    ) -> Self {
+        ClaimPayout {
+            relayer: relayer.to_formatted_string(),
+            recipient: recipient.to_formatted_string(),
+            amount: amount.to_string(),
             cep18_hash: cep18_hash.map(|hash| hash.to_formatted_string()),
         }
     }
@@ -8,15 +8,35 @@ compile_error!("target arch should be wasm32: compile with '--target wasm32-unkn
 // `no_std` environment.
 extern crate alloc;
 
+mod consent;
 mod constants;
+mod context;
+mod credit;
 mod errors;
+mod escrow;
 mod events;
+mod payout;
 mod permission;
+mod reservation;
+mod rules;
+mod swap;
 mod utils;
 
+use crate::context::{Context, StoredContext};
 use crate::errors::Error;
-use crate::events::{CallOnBehalf, Deposit, Register};
+use crate::events::{
+    CallOnBehalf, ClaimPayout, ConfirmPayment, CreditExpired, Deposit, EscrowPayment,
+    ExpireReservation, GrantCredit, LowBalance, Payment, Payout, ReclaimPayment, RefundPayment,
+    Register, RegisterPackage, Reserve, ResumeSponsorship, RevokeConsent, SetBudget,
+    SetCallerListMode, SetConsent, SetEntryPoints, SetEscrow, SetInjectedArgs, SetLowBalance,
+    SetOwnerRelayers, SetPackageVersion, SetPayout, SetPolicy, SetPostHook, SetQuota, SetSubsidy,
+    Settle, SuspendSponsorship, Swap, TopUp, UpdateCallerList, UpdateDeniedCallers,
+    UpdateDeniedContracts, UpdateDeniedPackages, UpdateRelayers, UseCredit, WalletDeposit,
+    WalletWithdraw,
+};
 use crate::permission::Permission;
+use crate::rules::CallerListMode;
+use crate::utils::RawValue;
 use alloc::boxed::Box;
 use alloc::string::{String, ToString};
 use alloc::vec;
@@ -26,11 +46,15 @@ use casper_contract::contract_api::{runtime, storage};
 use casper_contract::unwrap_or_revert::UnwrapOrRevert;
 use casper_event_standard::Schemas;
 use casper_types::account::AccountHash;
+use casper_types::bytesrepr::{self, Bytes, ToBytes};
 use casper_types::contracts::NamedKeys;
 use casper_types::{
-    runtime_args, ApiError, CLType, CLTyped, CLValue, ContractHash, EntryPoint, EntryPointAccess,
-    EntryPointType, EntryPoints, Key, Parameter, RuntimeArgs, URef, U256, U512,
+    runtime_args, ApiError, CLType, CLTyped, CLValue, ContractHash, ContractPackageHash,
+    EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, Key, Parameter, RuntimeArgs, URef,
+    U256, U512,
 };
+use core::cmp::min;
+use core::convert::TryFrom;
 use num_traits::AsPrimitive;
 
 #[no_mangle]
@@ -46,11 +70,55 @@ pub extern "C" fn call() {
 }
 
 fn init_events() {
-    let schemas = Schemas::new()
+    casper_event_standard::init(event_schemas());
+}
+
+fn event_schemas() -> Schemas {
+    Schemas::new()
         .with::<Register>()
+        .with::<RegisterPackage>()
+        .with::<SetPackageVersion>()
         .with::<Deposit>()
-        .with::<CallOnBehalf>();
-    casper_event_standard::init(schemas);
+        .with::<CallOnBehalf>()
+        .with::<SetInjectedArgs>()
+        .with::<SetSubsidy>()
+        .with::<SetEntryPoints>()
+        .with::<SetCallerListMode>()
+        .with::<UpdateCallerList>()
+        .with::<SetConsent>()
+        .with::<RevokeConsent>()
+        .with::<UpdateRelayers>()
+        .with::<SetOwnerRelayers>()
+        .with::<UpdateDeniedCallers>()
+        .with::<UpdateDeniedContracts>()
+        .with::<UpdateDeniedPackages>()
+        .with::<SetQuota>()
+        .with::<SetBudget>()
+        .with::<SetPolicy>()
+        .with::<SetPostHook>()
+        .with::<SuspendSponsorship>()
+        .with::<ResumeSponsorship>()
+        .with::<Swap>()
+        .with::<SetPayout>()
+        .with::<Payout>()
+        .with::<ClaimPayout>()
+        .with::<SetLowBalance>()
+        .with::<LowBalance>()
+        .with::<TopUp>()
+        .with::<GrantCredit>()
+        .with::<UseCredit>()
+        .with::<CreditExpired>()
+        .with::<WalletDeposit>()
+        .with::<WalletWithdraw>()
+        .with::<Payment>()
+        .with::<SetEscrow>()
+        .with::<EscrowPayment>()
+        .with::<ConfirmPayment>()
+        .with::<RefundPayment>()
+        .with::<ReclaimPayment>()
+        .with::<Reserve>()
+        .with::<Settle>()
+        .with::<ExpireReservation>()
 }
 
 #[no_mangle]
@@ -60,86 +128,468 @@ pub extern "C" fn init() {
     if caller != installer {
         runtime::revert(ApiError::from(Error::Unauthorized))
     }
-    _ = storage::new_dictionary(constants::KEY_REGISTERED_CONTRACT);
-    _ = storage::new_dictionary(constants::KEY_OWNER_BALANCE);
-    runtime::put_key(constants::KEY_PURSE, system::create_purse().into());
-    runtime::put_key(constants::KEY_DEPOSIT_PURSE, system::create_purse().into());
-    runtime::put_key(constants::KEY_FEE_PURSE, system::create_purse().into());
+    create_storage();
+}
+
+/// Creates the storage a relay upgraded from an older version lacks. Safe to call again.
+#[no_mangle]
+pub extern "C" fn migrate() {
+    permission::require(Permission::Installer);
+    create_storage();
+}
+
+/// Creates every dictionary, purse and named value the relay uses, skipping those that exist.
+fn create_storage() {
+    for name in constants::DICTIONARIES {
+        if runtime::get_key(name).is_none() {
+            storage::new_dictionary(name).unwrap_or_revert();
+        }
+    }
+    for name in [
+        constants::KEY_PURSE,
+        constants::KEY_DEPOSIT_PURSE,
+        constants::KEY_FEE_PURSE,
+        constants::KEY_PAYOUT_PURSE,
+        constants::KEY_CREDIT_PURSE,
+    ] {
+        if runtime::get_key(name).is_none() {
+            runtime::put_key(name, system::create_purse().into());
+        }
+    }
+    put_missing(constants::KEY_NONCE, 0u64);
+    put_missing(constants::KEY_CONTEXT, None::<StoredContext>);
+    put_missing(
+        constants::KEY_RESERVATION_TIMEOUT,
+        constants::DEFAULT_RESERVATION_TIMEOUT,
+    );
+    put_missing(constants::KEY_SWAP_ROUTER, None::<ContractPackageHash>);
+
+    // Upgraded relays keep their events, with the schemas extended to the new events
+    match runtime::get_key(casper_event_standard::EVENTS_SCHEMA) {
+        None => init_events(),
+        Some(key) => {
+            utils::write_event_schemas(key.into_uref().unwrap_or_revert(), event_schemas())
+        }
+    }
+}
 
-    init_events();
+fn put_missing<T: CLTyped + ToBytes>(name: &str, value: T) {
+    if runtime::get_key(name).is_none() {
+        runtime::put_key(name, storage::new_uref(value).into());
+    }
 }
 
 #[no_mangle]
 pub extern "C" fn call_on_behalf() {
-    permission::require(Permission::Installer);
-    let paymaster = runtime::get_caller();
+    permission::require(Permission::Relayer);
 
     let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
     let entry_point: String = runtime::get_named_arg(constants::ARG_ENTRY_POINT);
-    let caller: AccountHash = runtime::get_named_arg(constants::ARG_CALLER);
+    let caller: Key = runtime::get_named_arg(constants::ARG_CALLER);
     let gas_amount: U512 = runtime::get_named_arg(constants::ARG_GAS_AMOUNT);
     let pay_amount: U512 = runtime::get_named_arg(constants::ARG_PAY_AMOUNT);
     let cep18_hash = utils::get_optional_named_arg::<ContractHash>(constants::ARG_CEP18_HASH);
+    let args: RuntimeArgs = runtime::get_named_arg(constants::ARG_ARGS);
 
-    // Check if recipient contract is registered
-    let owner = utils::get_storage_dic::<AccountHash>(
-        utils::get_uref(constants::KEY_REGISTERED_CONTRACT),
-        contract_hash.to_string().as_str(),
-    )
-    .unwrap_or_revert_with(ApiError::from(Error::Unregistered));
+    let result = relay_call(
+        contract_hash,
+        entry_point,
+        args,
+        pay_amount,
+        caller,
+        gas_amount,
+        cep18_hash,
+    );
+
+    // Forward whatever the target returned as the relay's own result
+    runtime::ret(result.into_cl_value())
+}
+
+/// Relays a sequence of calls for one caller in a single deploy. Each call is checked, the batch's
+/// gas is charged once, all calls must target contracts of the same owner so no owner pays for
+/// another's calls, and any failing call reverts the whole batch.
+#[no_mangle]
+pub extern "C" fn call_on_behalf_batch() {
+    permission::require(Permission::Relayer);
+
+    let calls: Vec<(ContractHash, String, (Bytes, U512))> =
+        runtime::get_named_arg(constants::ARG_CALLS);
+    let caller: Key = runtime::get_named_arg(constants::ARG_CALLER);
+    let mut gas_amount: U512 = runtime::get_named_arg(constants::ARG_GAS_AMOUNT);
+    let cep18_hash = utils::get_optional_named_arg::<ContractHash>(constants::ARG_CEP18_HASH);
+    let owner = match calls.first() {
+        Some((contract_hash, _, _)) => get_owner(*contract_hash),
+        None => runtime::revert(ApiError::InvalidArgument),
+    };
+    if calls
+        .iter()
+        .any(|(contract_hash, _, _)| get_owner(*contract_hash) != owner)
+    {
+        runtime::revert(ApiError::from(Error::BatchOwnerMismatch))
+    }
+
+    // The whole batch's gas is charged once, with the first call, to their single owner
+    for (contract_hash, entry_point, (args, pay_amount)) in calls {
+        let args: RuntimeArgs = bytesrepr::deserialize(args.into()).unwrap_or_revert();
+        relay_call(
+            contract_hash,
+            entry_point,
+            args,
+            pay_amount,
+            caller,
+            gas_amount,
+            cep18_hash,
+        );
+        gas_amount = U512::zero();
+    }
+}
+
+/// Locks the owner's gas and fee for a call before running it, so the relayer is paid by `settle`
+/// even if the call reverts.
+#[no_mangle]
+pub extern "C" fn reserve() {
+    permission::require(Permission::Relayer);
+    let relayer = runtime::get_caller();
+
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    let entry_point: String = runtime::get_named_arg(constants::ARG_ENTRY_POINT);
+    let caller: Key = runtime::get_named_arg(constants::ARG_CALLER);
+    let gas_amount: U512 = runtime::get_named_arg(constants::ARG_GAS_AMOUNT);
+    let pay_amount: U512 = runtime::get_named_arg(constants::ARG_PAY_AMOUNT);
+    let args: RuntimeArgs = runtime::get_named_arg(constants::ARG_ARGS);
+
+    let owner = check_call(contract_hash, &entry_point, &args, caller, gas_amount);
+    // Reservations are fully sponsored, so they can't get around a per-caller subsidy cap
+    if get_subsidy(contract_hash).is_some() {
+        runtime::revert(ApiError::from(Error::ReservationNotAllowed))
+    }
+    rules::check_owner_relayer(owner, relayer);
+    rules::use_budget(contract_hash, gas_amount);
+    consent::use_spend(caller, contract_hash, pay_amount);
+
+    let request_id = context::next_nonce();
+    let fee = calculate_fee(gas_amount);
+    let args = Bytes::from(args.to_bytes().unwrap_or_revert());
+    let deadline = reservation::reserve(
+        request_id,
+        owner,
+        relayer,
+        gas_amount,
+        fee,
+        (caller, contract_hash, (entry_point, args, pay_amount)),
+    );
+    check_low_balance(owner);
+
+    casper_event_standard::emit(Reserve::new(
+        request_id,
+        contract_hash,
+        owner,
+        relayer,
+        gas_amount + fee,
+        deadline,
+    ));
+    runtime::ret(CLValue::from_t(request_id).unwrap_or_revert())
+}
+
+/// Runs a reserved call once. Gas was already locked by `reserve`, so nothing is charged here.
+#[no_mangle]
+pub extern "C" fn call_reserved() {
+    let request_id: u64 = runtime::get_named_arg(constants::ARG_REQUEST_ID);
+    let (_, (owner, relayer), (gas_amount, fee, deadline)) = reservation::get(request_id);
+    // A relayer removed since it reserved can't run the call anymore
+    permission::require(Permission::Relayer);
+    if runtime::get_caller() != relayer {
+        runtime::revert(ApiError::from(Error::Unauthorized))
+    }
+    if u64::from(runtime::get_blocktime()) >= deadline {
+        runtime::revert(ApiError::from(Error::ReservationExpired))
+    }
+
+    let (caller, contract_hash, (entry_point, args, pay_amount)) =
+        reservation::take_call(request_id);
+    // Suspensions and denials made since the reservation still apply to it
+    rules::check_denylist(contract_hash, caller);
+    rules::check_suspended(contract_hash, owner);
+    let args: RuntimeArgs = bytesrepr::deserialize(args.into()).unwrap_or_revert();
+    let context = Context {
+        caller,
+        owner,
+        relayer,
+        nonce: request_id,
+    };
+    let result = execute_call(contract_hash, &entry_point, args, pay_amount, &context);
+
+    rules::call_post_hook(contract_hash, caller, owner, &entry_point, gas_amount, fee);
+
+    casper_event_standard::emit(CallOnBehalf::new(
+        contract_hash,
+        owner,
+        caller,
+        entry_point,
+        gas_amount,
+        gas_amount,
+        U512::zero(),
+        None,
+        result.hash(),
+    ));
+    runtime::ret(result.into_cl_value())
+}
+
+/// Pays the reserving relayer for the gas it used, whether the reserved call succeeded or not, and
+/// returns the rest of the reservation to the owner.
+#[no_mangle]
+pub extern "C" fn settle() {
+    let request_id: u64 = runtime::get_named_arg(constants::ARG_REQUEST_ID);
+    let gas_used: U512 = runtime::get_named_arg(constants::ARG_GAS_USED);
+    let (contract_hash, (owner, relayer), (gas_amount, fee, deadline)) =
+        reservation::get(request_id);
+    if runtime::get_caller() != relayer {
+        runtime::revert(ApiError::from(Error::Unauthorized))
+    }
+    if u64::from(runtime::get_blocktime()) >= deadline {
+        runtime::revert(ApiError::from(Error::ReservationExpired))
+    }
+    // Settling a call that never ran only releases the reservation
+    let gas_used = if reservation::is_called(request_id) {
+        gas_used
+    } else {
+        U512::zero()
+    };
+    reservation::take(request_id);
+
+    let paid = min(gas_used, gas_amount);
+    let paid_fee = min(calculate_fee(paid), fee);
+    payout::pay_cspr(relayer, paid);
+    if paid_fee > U512::zero() {
+        system::transfer_from_purse_to_purse(
+            utils::get_uref(constants::KEY_DEPOSIT_PURSE),
+            utils::get_uref(constants::KEY_FEE_PURSE),
+            paid_fee,
+            None,
+        )
+        .unwrap_or_revert();
+    }
+    let refund = gas_amount + fee - paid - paid_fee;
+    reservation::refund(owner, refund);
+    rules::release_budget(contract_hash, gas_amount - paid);
+
+    casper_event_standard::emit(Settle::new(request_id, relayer, paid, paid_fee, refund));
+}
+
+/// Returns an unsettled reservation to its owner once it has expired. Anyone may call it.
+#[no_mangle]
+pub extern "C" fn expire_reservation() {
+    let request_id: u64 = runtime::get_named_arg(constants::ARG_REQUEST_ID);
+    let (contract_hash, (owner, _), (gas_amount, fee, deadline)) = reservation::get(request_id);
+    if u64::from(runtime::get_blocktime()) < deadline {
+        runtime::revert(ApiError::from(Error::ReservationActive))
+    }
+    reservation::take(request_id);
+    reservation::refund(owner, gas_amount + fee);
+    rules::release_budget(contract_hash, gas_amount);
+
+    casper_event_standard::emit(ExpireReservation::new(request_id, owner, gas_amount + fee));
+}
+
+#[no_mangle]
+pub extern "C" fn set_reservation_timeout() {
+    permission::require(Permission::Installer);
+
+    let timeout: u64 = runtime::get_named_arg(constants::ARG_TIMEOUT);
+    utils::write_storage(constants::KEY_RESERVATION_TIMEOUT, timeout)
+}
+
+/// Checks, charges and executes one relayed call, reimbursing the relayer calling the relay.
+fn relay_call(
+    contract_hash: ContractHash,
+    entry_point: String,
+    args: RuntimeArgs,
+    pay_amount: U512,
+    caller: Key,
+    gas_amount: U512,
+    cep18_hash: Option<ContractHash>,
+) -> RawValue {
+    let paymaster = runtime::get_caller();
+    let owner = check_call(contract_hash, &entry_point, &args, caller, gas_amount);
+
+    // Owner covers gas up to the subsidy allowance, the caller pays the rest
+    let owner_amount = use_subsidy(contract_hash, caller, gas_amount);
+    let caller_amount = gas_amount - owner_amount;
+    if owner_amount > U512::zero() {
+        rules::check_owner_relayer(owner, paymaster);
+        rules::use_budget(contract_hash, owner_amount);
+        // Promotional credits are spent before the owner's own balance
+        let credit = match cep18_hash {
+            Some(_) => U512::zero(),
+            None => credit::spend(owner, owner_amount + calculate_fee(owner_amount)),
+        };
+        pay_gas(
+            owner,
+            constants::KEY_OWNER_BALANCE,
+            owner_amount,
+            credit,
+            cep18_hash,
+            paymaster,
+        );
+        if cep18_hash.is_none() {
+            check_low_balance(owner);
+        }
+    }
+    // Everything charged to the caller counts against the spend limit of its consent
+    consent::use_spend(
+        caller,
+        contract_hash,
+        caller_amount + calculate_fee(caller_amount) + pay_amount,
+    );
+    if caller_amount > U512::zero() {
+        pay_gas(
+            caller,
+            constants::KEY_CALLER_BALANCE,
+            caller_amount,
+            U512::zero(),
+            cep18_hash,
+            paymaster,
+        );
+    }
+
+    let context = Context {
+        caller,
+        owner,
+        relayer: paymaster,
+        nonce: context::next_nonce(),
+    };
+    let result = execute_call(contract_hash, &entry_point, args, pay_amount, &context);
+
+    let fee = calculate_fee(owner_amount) + calculate_fee(caller_amount);
+    rules::call_post_hook(contract_hash, caller, owner, &entry_point, gas_amount, fee);
+
+    casper_event_standard::emit(CallOnBehalf::new(
+        contract_hash,
+        owner,
+        caller,
+        entry_point,
+        gas_amount,
+        owner_amount,
+        caller_amount,
+        cep18_hash,
+        result.hash(),
+    ));
+
+    result
+}
 
+/// Runs the sponsorship and consent checks of a relayed call, and returns the owner of the target contract.
+fn check_call(
+    contract_hash: ContractHash,
+    entry_point: &str,
+    args: &RuntimeArgs,
+    caller: Key,
+    gas_amount: U512,
+) -> Key {
+    // Callers are accounts or contract packages, as seen by a directly called contract
+    if !matches!(caller, Key::Account(_) | Key::Hash(_)) {
+        runtime::revert(ApiError::InvalidArgument)
+    }
+    rules::check_denylist(contract_hash, caller);
+
+    let owner = get_owner(contract_hash);
+
+    rules::check_suspended(contract_hash, owner);
+    rules::check_entry_point(contract_hash, entry_point, gas_amount);
+    rules::check_caller(contract_hash, caller);
+    rules::use_quota(contract_hash, caller, gas_amount);
+    rules::check_policy(contract_hash, caller, entry_point, args, gas_amount);
+    consent::use_consent(caller, contract_hash, entry_point);
+    owner
+}
+
+/// Returns the owner of a registered contract, reverting if it isn't registered.
+fn get_owner(contract_hash: ContractHash) -> Key {
+    utils::get_registered_owner(contract_hash)
+        .unwrap_or_revert_with(ApiError::from(Error::Unregistered))
+}
+
+/// Sends the call's payment, then calls the target within the relay context.
+fn execute_call(
+    contract_hash: ContractHash,
+    entry_point: &str,
+    mut args: RuntimeArgs,
+    pay_amount: U512,
+    context: &Context,
+) -> RawValue {
+    let caller = context.caller;
+    // The relay nonce doubles as the request id of escrowed payments
+    let nonce = context.nonce;
+    if pay_amount > U512::zero() {
+        debit_wallet(caller, pay_amount);
+        match escrow::get_timeout(contract_hash) {
+            Some(timeout) => {
+                let deadline = escrow::hold(nonce, contract_hash, caller, pay_amount, timeout);
+                casper_event_standard::emit(EscrowPayment::new(
+                    nonce,
+                    contract_hash,
+                    caller,
+                    pay_amount,
+                    deadline,
+                ));
+            }
+            None => {
+                pay_recipient(contract_hash, pay_amount);
+                casper_event_standard::emit(Payment::new(contract_hash, caller, pay_amount));
+            }
+        }
+    }
+
+    context::inject(contract_hash, &mut args, context);
+
+    let previous = context::enter(context);
+    let result: RawValue = utils::call_registered(contract_hash, entry_point, args);
+    context::exit(previous);
+    result
+}
+
+fn calculate_fee(gas_amount: U512) -> U512 {
     let fee_rate = utils::get_storage::<u32>(constants::KEY_FEE_RATE);
-    let fee = gas_amount
+    gas_amount
         .checked_mul(U512::from(fee_rate))
         .unwrap_or_revert()
         .checked_div(U512::from(1000))
-        .unwrap_or_revert();
+        .unwrap_or_revert()
+}
 
+/// Charges `gas_amount` plus fee to `payer` and reimburses the paymaster, either in CEP18 from the
+/// payer's allowance or in CSPR from the payer's balance in the `ledger` dictionary. `credit` is
+/// CSPR already moved into the deposit purse on the payer's behalf.
+fn pay_gas(
+    payer: Key,
+    ledger: &str,
+    gas_amount: U512,
+    credit: U512,
+    cep18_hash: Option<ContractHash>,
+    paymaster: AccountHash,
+) {
+    let fee = calculate_fee(gas_amount);
     match cep18_hash {
         Some(cep18_hash) => {
             // Pay gas fee in CEP18
-            let contract_package = utils::get_contract_package().unwrap_or_revert();
-            let allowance: U256 = runtime::call_contract(
-                cep18_hash,
-                constants::ENTRY_POINT_ALLOWANCE,
-                runtime_args! {
-                    constants::ARG_OWNER => Key::from(owner),
-                    constants::ARG_SPENDER => Key::from(contract_package),
-                },
-            );
-            let allowance: U512 = allowance.as_();
+            let allowance: U512 = get_allowance(cep18_hash, payer).as_();
             if allowance < gas_amount + fee {
                 runtime::revert(ApiError::from(Error::InsufficientBalance))
             }
 
-            let _: () = runtime::call_contract(
-                cep18_hash,
-                constants::ENTRY_POINT_TRANSFER_FROM,
-                runtime_args! {
-                    constants::ARG_OWNER => Key::from(owner),
-                    constants::ARG_RECIPIENT => Key::from(paymaster),
-                    constants::ARG_AMOUNT => gas_amount,
-                },
-            );
+            payout::pay_cep18(paymaster, payer, cep18_hash, gas_amount);
         }
         None => {
             // Pay gas fee in CSPR
-            let owner_balance = utils::get_storage_dic::<U512>(
-                utils::get_uref(constants::KEY_OWNER_BALANCE),
-                owner.to_string().as_str(),
-            )
-            .unwrap();
+            let balance =
+                utils::get_storage_dic::<U512>(utils::get_uref(ledger), &utils::key_to_str(&payer))
+                    .unwrap_or_default();
 
-            if owner_balance < gas_amount + fee {
+            if balance + credit < gas_amount + fee {
                 runtime::revert(ApiError::from(Error::InsufficientBalance))
             }
 
-            let _ = system::transfer_from_purse_to_account(
-                utils::get_uref(constants::KEY_DEPOSIT_PURSE),
-                paymaster,
-                gas_amount,
-                None,
-            );
+            payout::pay_cspr(paymaster, gas_amount);
             if fee > U512::zero() {
                 let _ = system::transfer_from_purse_to_purse(
                     utils::get_uref(constants::KEY_DEPOSIT_PURSE),
@@ -149,61 +599,264 @@ pub extern "C" fn call_on_behalf() {
                 );
             }
             utils::write_storage_dic(
-                utils::get_uref(constants::KEY_OWNER_BALANCE),
-                owner.to_string().as_str(),
-                owner_balance - gas_amount - fee,
+                utils::get_uref(ledger),
+                &utils::key_to_str(&payer),
+                balance + credit - gas_amount - fee,
             );
         }
     }
+}
 
-    if pay_amount > U512::zero() {
-        let purse = utils::get_uref(constants::KEY_PURSE);
-        let recipient_purse: URef = runtime::call_contract(
-            contract_hash,
-            constants::ENTRY_POINT_GET_PURSE,
-            runtime_args! {},
-        );
-        let _ = system::transfer_from_purse_to_purse(purse, recipient_purse, pay_amount, None);
+/// Takes `amount` of CSPR out of the caller's wallet. The CSPR itself stays in the deposit purse.
+fn debit_wallet(caller: Key, amount: U512) {
+    let balance = utils::get_storage_dic::<U512>(
+        utils::get_uref(constants::KEY_CALLER_BALANCE),
+        &utils::key_to_str(&caller),
+    )
+    .unwrap_or_default();
+    if balance < amount {
+        runtime::revert(ApiError::from(Error::InsufficientBalance))
     }
 
-    let mut args: RuntimeArgs = runtime::get_named_arg(constants::ARG_ARGS);
-    args.insert(constants::ARG_CALLER, caller)
-        .unwrap_or_revert_with(ApiError::InvalidArgument);
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_CALLER_BALANCE),
+        &utils::key_to_str(&caller),
+        balance - amount,
+    );
+}
 
-    let _: () = runtime::call_contract(contract_hash, entry_point.as_str(), args);
+/// Returns `amount` of CSPR held in the deposit purse to the caller's wallet.
+fn credit_wallet(caller: Key, amount: U512) {
+    let balance = utils::get_storage_dic::<U512>(
+        utils::get_uref(constants::KEY_CALLER_BALANCE),
+        &utils::key_to_str(&caller),
+    )
+    .unwrap_or_default();
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_CALLER_BALANCE),
+        &utils::key_to_str(&caller),
+        balance + amount,
+    );
+}
 
-    casper_event_standard::emit(CallOnBehalf::new(
+/// Sends `pay_amount` of CSPR from the deposit purse to the registered contract's purse.
+fn pay_recipient(contract_hash: ContractHash, pay_amount: U512) {
+    let recipient_purse: URef = utils::call_registered(
         contract_hash,
-        owner,
-        caller,
-        entry_point,
-        gas_amount,
+        constants::ENTRY_POINT_GET_PURSE,
+        runtime_args! {},
+    );
+    system::transfer_from_purse_to_purse(
+        utils::get_uref(constants::KEY_DEPOSIT_PURSE),
+        recipient_purse,
+        pay_amount,
+        None,
+    )
+    .unwrap_or_revert();
+}
+
+/// Returns how many tokens `owner` allows the relay to spend.
+fn get_allowance(cep18_hash: ContractHash, owner: Key) -> U256 {
+    let contract_package = utils::get_contract_package().unwrap_or_revert();
+    runtime::call_contract(
         cep18_hash,
-    ));
+        constants::ENTRY_POINT_ALLOWANCE,
+        runtime_args! {
+            constants::ARG_OWNER => owner,
+            constants::ARG_SPENDER => Key::from(contract_package),
+        },
+    )
+}
+
+/// Returns the owner's balance and whether it is below the owner's low-balance threshold.
+fn get_low_balance(owner: Key) -> (U512, U512, bool) {
+    let threshold = utils::get_storage_dic::<U512>(
+        utils::get_uref(constants::KEY_LOW_BALANCE),
+        &utils::key_to_str(&owner),
+    )
+    .unwrap_or_default();
+    let balance = utils::get_storage_dic::<U512>(
+        utils::get_uref(constants::KEY_OWNER_BALANCE),
+        &utils::key_to_str(&owner),
+    )
+    .unwrap_or_default();
+    (balance, threshold, balance < threshold)
+}
+
+/// Emits `LowBalance` when the owner's balance is below its threshold. The top-up itself runs in
+/// `top_up`, as a failing swap would otherwise revert the relayed call.
+fn check_low_balance(owner: Key) {
+    let (balance, threshold, low) = get_low_balance(owner);
+    if low {
+        casper_event_standard::emit(LowBalance::new(owner, balance, threshold));
+    }
+}
+
+/// Tops a low owner balance up by swapping tokens from the owner's allowance as configured with
+/// `set_low_balance`. The owner or a relayer may trigger it, and it does nothing while the balance
+/// isn't low.
+#[no_mangle]
+pub extern "C" fn top_up() {
+    let owner: Key = runtime::get_named_arg(constants::ARG_OWNER);
+    // Only the owner or a relayer picks when the owner's tokens get swapped
+    if utils::get_immediate_caller() != owner {
+        permission::require(Permission::Relayer);
+    }
+    let (_, _, low) = get_low_balance(owner);
+    if !low {
+        return;
+    }
+
+    let (path, amount_in, amount_out_min) = match utils::get_storage_dic::<(Vec<Key>, U256, U512)>(
+        utils::get_uref(constants::KEY_TOP_UP),
+        &utils::key_to_str(&owner),
+    ) {
+        Some(top_up) if !top_up.0.is_empty() => top_up,
+        _ => return,
+    };
+    let cep18_hash: ContractHash = path[0]
+        .into_hash()
+        .unwrap_or_revert_with(ApiError::InvalidArgument)
+        .into();
+    let (purse, amount_out) =
+        swap::swap_to_cspr(owner, cep18_hash, amount_in, amount_out_min, path);
+    system::transfer_from_purse_to_purse(
+        purse,
+        utils::get_uref(constants::KEY_DEPOSIT_PURSE),
+        amount_out,
+        None,
+    )
+    .unwrap_or_revert();
+    // The router may have called back into the relay, so the balance is read after the swap
+    let (balance, _, _) = get_low_balance(owner);
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_OWNER_BALANCE),
+        &utils::key_to_str(&owner),
+        balance + amount_out,
+    );
+
+    casper_event_standard::emit(TopUp::new(owner, cep18_hash, amount_in, amount_out));
+}
+
+/// Returns the contract's per-caller gas allowance and its period, `None` if it sponsors everything.
+fn get_subsidy(contract_hash: ContractHash) -> Option<(U512, u64)> {
+    utils::get_storage_dic::<Option<(U512, u64)>>(
+        utils::get_uref(constants::KEY_SUBSIDY),
+        contract_hash.to_string().as_str(),
+    )
+    .flatten()
+}
+
+/// Consumes the caller's subsidy allowance on `contract_hash` and returns the part of `gas_amount`
+/// the owner covers. Without a subsidy rule the owner covers everything.
+fn use_subsidy(contract_hash: ContractHash, caller: Key, gas_amount: U512) -> U512 {
+    let (allowance, period) = match get_subsidy(contract_hash) {
+        Some(subsidy) => subsidy,
+        None => return gas_amount,
+    };
+
+    let usage_uref = utils::get_uref(constants::KEY_SUBSIDY_USAGE);
+    let usage_key = utils::get_dictionary_key(&(contract_hash, caller));
+    let now: u64 = runtime::get_blocktime().into();
+    let (mut used, mut period_start) =
+        utils::get_storage_dic::<(U512, u64)>(usage_uref, &usage_key)
+            .unwrap_or((U512::zero(), now));
+    // A zero period means the allowance is a lifetime one
+    if period > 0 && now >= period_start.saturating_add(period) {
+        used = U512::zero();
+        period_start = now;
+    }
+
+    let covered = min(allowance.saturating_sub(used), gas_amount);
+    utils::write_storage_dic(usage_uref, &usage_key, (used + covered, period_start));
+    covered
 }
 
 #[no_mangle]
 pub extern "C" fn register() {
-    let owner: AccountHash = runtime::get_named_arg(constants::ARG_OWNER);
+    let owner: Key = runtime::get_named_arg(constants::ARG_OWNER);
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    let is_target = matches!(
+        utils::get_immediate_caller_contract(),
+        Some((_, caller_hash)) if caller_hash == contract_hash
+    );
+    write_registration(contract_hash, owner, is_target);
+
+    casper_event_standard::emit(Register::new(contract_hash, owner));
+}
+
+/// Registers a contract package, so relayed calls keep following its upgrades. Its settings are
+/// then managed with the package hash passed as `contract`.
+#[no_mangle]
+pub extern "C" fn register_package() {
+    let owner: Key = runtime::get_named_arg(constants::ARG_OWNER);
+    let contract_package_hash: ContractPackageHash =
+        runtime::get_named_arg(constants::ARG_CONTRACT_PACKAGE);
+    let contract_hash = utils::package_registration(contract_package_hash);
+    let is_target = utils::get_immediate_caller() == Key::from(contract_package_hash);
+    write_registration(contract_hash, owner, is_target);
+    // Re-registering keeps the pinned version unless a new one is given, and new registrations
+    // call the latest version until the owner pins one
+    let version = utils::get_optional_named_arg::<u32>(constants::ARG_VERSION);
+    if version.is_some() || !utils::is_package_registration(contract_hash) {
+        utils::write_storage_dic(
+            utils::get_uref(constants::KEY_PACKAGE_VERSION),
+            contract_hash.to_string().as_str(),
+            version,
+        );
+    }
+
+    casper_event_standard::emit(RegisterPackage::new(contract_package_hash, owner));
+}
+
+#[no_mangle]
+pub extern "C" fn set_package_version() {
+    let contract_package_hash: ContractPackageHash =
+        runtime::get_named_arg(constants::ARG_CONTRACT_PACKAGE);
+    let contract_hash = utils::package_registration(contract_package_hash);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let version: Option<u32> = runtime::get_named_arg(constants::ARG_VERSION);
+    let versions = utils::get_uref(constants::KEY_PACKAGE_VERSION);
+    if utils::get_storage_dic::<Option<u32>>(versions, contract_hash.to_string().as_str()).is_none()
+    {
+        runtime::revert(Error::Unregistered)
+    }
+    utils::write_storage_dic(versions, contract_hash.to_string().as_str(), version);
+
+    casper_event_standard::emit(SetPackageVersion::new(contract_package_hash, version));
+}
+
+/// Registers `owner` for the contract. Only the target itself (`is_target`), the installer or the
+/// current owner may do so, so nobody can claim a contract they don't control.
+fn write_registration(contract_hash: ContractHash, owner: Key, is_target: bool) {
+    rules::check_denylist(contract_hash, owner);
+    let immediate_caller = utils::get_immediate_caller();
+    let installer = utils::get_storage::<AccountHash>(constants::KEY_INSTALLER);
+    let current_owner = utils::get_registered_owner(contract_hash);
+    if !is_target
+        && immediate_caller != Key::from(installer)
+        && current_owner != Some(immediate_caller)
+    {
+        runtime::revert(ApiError::from(Error::Unauthorized))
+    }
+
     let owner_balance = utils::get_storage_dic::<U512>(
         utils::get_uref(constants::KEY_OWNER_BALANCE),
-        owner.to_string().as_str(),
+        &utils::key_to_str(&owner),
     );
     if owner_balance.is_none() {
         utils::write_storage_dic(
             utils::get_uref(constants::KEY_OWNER_BALANCE),
-            owner.to_string().as_str(),
+            &utils::key_to_str(&owner),
             U512::zero(),
         );
     }
-    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
     utils::write_storage_dic(
         utils::get_uref(constants::KEY_REGISTERED_CONTRACT),
         contract_hash.to_string().as_str(),
         owner,
     );
-
-    casper_event_standard::emit(Register::new(contract_hash, owner));
 }
 
 #[no_mangle]
@@ -237,114 +890,1064 @@ pub extern "C" fn claim_fee() {
 }
 
 #[no_mangle]
-pub extern "C" fn deposit() {
-    let owner: AccountHash = runtime::get_named_arg(constants::ARG_OWNER);
-    let owner_balance = utils::get_storage_dic::<U512>(
+pub extern "C" fn set_low_balance() {
+    let owner = utils::get_immediate_caller();
+    utils::get_storage_dic::<U512>(
         utils::get_uref(constants::KEY_OWNER_BALANCE),
-        owner.to_string().as_str(),
+        &utils::key_to_str(&owner),
     )
     .unwrap_or_revert_with(ApiError::from(Error::Unregistered));
 
-    let purse = runtime::get_key(constants::KEY_PURSE)
-        .unwrap_or_revert()
-        .into_uref()
-        .unwrap_or_revert();
-    let purse_balance = system::get_purse_balance(purse).unwrap_or_revert();
-
-    system::transfer_from_purse_to_purse(
-        purse,
-        utils::get_uref(constants::KEY_DEPOSIT_PURSE),
-        purse_balance,
-        None,
-    )
-    .unwrap_or_revert();
-
+    let threshold: U512 = runtime::get_named_arg(constants::ARG_THRESHOLD);
+    // An empty path disables the automatic top-up
+    let path: Vec<Key> = runtime::get_named_arg(constants::ARG_PATH);
+    let amount_in: U256 = runtime::get_named_arg(constants::ARG_AMOUNT_IN);
+    let amount_out_min: U512 = runtime::get_named_arg(constants::ARG_AMOUNT_OUT_MIN);
+    let top_up = !path.is_empty();
     utils::write_storage_dic(
-        utils::get_uref(constants::KEY_OWNER_BALANCE),
-        owner.to_string().as_str(),
-        owner_balance + purse_balance,
+        utils::get_uref(constants::KEY_LOW_BALANCE),
+        &utils::key_to_str(&owner),
+        threshold,
+    );
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_TOP_UP),
+        &utils::key_to_str(&owner),
+        (path, amount_in, amount_out_min),
+    );
+
+    casper_event_standard::emit(SetLowBalance::new(owner, threshold, top_up));
+}
+
+#[no_mangle]
+pub extern "C" fn set_payout() {
+    permission::require(Permission::Relayer);
+    permission::require(Permission::Session);
+    let relayer = runtime::get_caller();
+
+    let destination: Option<Key> = runtime::get_named_arg(constants::ARG_DESTINATION);
+    let accumulate: bool = runtime::get_named_arg(constants::ARG_ACCUMULATE);
+    payout::set_destination(relayer, destination, accumulate);
+
+    casper_event_standard::emit(SetPayout::new(relayer, destination, accumulate));
+}
+
+#[no_mangle]
+pub extern "C" fn claim_payout() {
+    permission::require(Permission::Session);
+    let relayer = runtime::get_caller();
+
+    let cep18_hash: Option<ContractHash> = runtime::get_named_arg(constants::ARG_CEP18_HASH);
+    let recipient: Key = runtime::get_named_arg(constants::ARG_RECIPIENT);
+    let amount = payout::claim(relayer, cep18_hash, recipient);
+
+    casper_event_standard::emit(ClaimPayout::new(relayer, recipient, amount, cep18_hash));
+}
+
+#[no_mangle]
+pub extern "C" fn set_swap_router() {
+    permission::require(Permission::Installer);
+
+    let router: Option<ContractPackageHash> = runtime::get_named_arg(constants::ARG_ROUTER);
+    utils::write_storage(constants::KEY_SWAP_ROUTER, router)
+}
+
+/// Swaps CEP18 tokens collected by the relayer into CSPR through the configured router.
+#[no_mangle]
+pub extern "C" fn swap_tokens() {
+    permission::require(Permission::Installer);
+    permission::require(Permission::Session);
+    let relayer = runtime::get_caller();
+
+    let cep18_hash: ContractHash = runtime::get_named_arg(constants::ARG_CEP18_HASH);
+    let amount_in: U256 = runtime::get_named_arg(constants::ARG_AMOUNT_IN);
+    let amount_out_min: U512 = runtime::get_named_arg(constants::ARG_AMOUNT_OUT_MIN);
+    let path: Vec<Key> = runtime::get_named_arg(constants::ARG_PATH);
+    let to_fee_purse: bool = runtime::get_named_arg(constants::ARG_TO_FEE_PURSE);
+    let (purse, amount_out) = swap::swap_to_cspr(
+        Key::from(relayer),
+        cep18_hash,
+        amount_in,
+        amount_out_min,
+        path,
+    );
+
+    if to_fee_purse {
+        system::transfer_from_purse_to_purse(
+            purse,
+            utils::get_uref(constants::KEY_FEE_PURSE),
+            amount_out,
+            None,
+        )
+        .unwrap_or_revert();
+    } else {
+        system::transfer_from_purse_to_account(purse, relayer, amount_out, None).unwrap_or_revert();
+    }
+
+    casper_event_standard::emit(Swap::new(
+        relayer,
+        cep18_hash,
+        amount_in,
+        amount_out,
+        to_fee_purse,
+    ));
+}
+
+#[no_mangle]
+pub extern "C" fn set_entry_points() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let restricted: bool = runtime::get_named_arg(constants::ARG_RESTRICTED);
+    let entry_points: Vec<(String, Option<U512>)> =
+        runtime::get_named_arg(constants::ARG_ENTRY_POINTS);
+    rules::set_entry_points(contract_hash, restricted, &entry_points);
+
+    casper_event_standard::emit(SetEntryPoints::new(
+        contract_hash,
+        restricted,
+        entry_points
+            .into_iter()
+            .map(|(entry_point, _)| entry_point)
+            .collect(),
+    ));
+}
+
+#[no_mangle]
+pub extern "C" fn set_caller_list_mode() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let mode: u8 = runtime::get_named_arg(constants::ARG_MODE);
+    rules::set_caller_list_mode(
+        contract_hash,
+        CallerListMode::try_from(mode).unwrap_or_revert(),
+    );
+
+    casper_event_standard::emit(SetCallerListMode::new(contract_hash, mode));
+}
+
+#[no_mangle]
+pub extern "C" fn update_caller_list() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let denylist: bool = runtime::get_named_arg(constants::ARG_DENYLIST);
+    let add: Vec<Key> = runtime::get_named_arg(constants::ARG_ADD);
+    let remove: Vec<Key> = runtime::get_named_arg(constants::ARG_REMOVE);
+    let mode = if denylist {
+        CallerListMode::Denylist
+    } else {
+        CallerListMode::Allowlist
+    };
+    rules::update_caller_list(contract_hash, mode, &add, &remove);
+
+    casper_event_standard::emit(UpdateCallerList::new(
+        contract_hash,
+        denylist,
+        &add,
+        &remove,
+    ));
+}
+
+#[no_mangle]
+pub extern "C" fn set_quota() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let window: u64 = runtime::get_named_arg(constants::ARG_WINDOW);
+    let max_calls: u64 = runtime::get_named_arg(constants::ARG_MAX_CALLS);
+    let max_gas: U512 = runtime::get_named_arg(constants::ARG_MAX_GAS);
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_QUOTA),
+        contract_hash.to_string().as_str(),
+        (window, max_calls, max_gas),
+    );
+
+    casper_event_standard::emit(SetQuota::new(contract_hash, window, max_calls, max_gas));
+}
+
+#[no_mangle]
+pub extern "C" fn set_budget() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let amount: Option<U512> = runtime::get_named_arg(constants::ARG_AMOUNT);
+    let period: u64 = runtime::get_named_arg(constants::ARG_PERIOD);
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_BUDGET),
+        contract_hash.to_string().as_str(),
+        amount.map(|amount| (amount, period)),
+    );
+    // Start a new budget with nothing spent
+    let now: u64 = runtime::get_blocktime().into();
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_BUDGET_USAGE),
+        contract_hash.to_string().as_str(),
+        (U512::zero(), now),
+    );
+
+    casper_event_standard::emit(SetBudget::new(contract_hash, amount, period));
+}
+
+#[no_mangle]
+pub extern "C" fn get_budget() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    runtime::ret(CLValue::from_t(rules::remaining_budget(contract_hash)).unwrap_or_revert());
+}
+
+#[no_mangle]
+pub extern "C" fn set_policy() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let policy: Option<ContractPackageHash> = runtime::get_named_arg(constants::ARG_POLICY);
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_POLICY),
+        contract_hash.to_string().as_str(),
+        policy,
+    );
+
+    casper_event_standard::emit(SetPolicy::new(contract_hash, policy));
+}
+
+/// Lets the caller consent to relayed calls of a registered contract's entry points in its name.
+#[no_mangle]
+pub extern "C" fn set_consent() {
+    let caller = utils::get_immediate_caller();
+
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    let entry_points: Vec<String> = runtime::get_named_arg(constants::ARG_ENTRY_POINTS);
+    let expiry: Option<u64> = runtime::get_named_arg(constants::ARG_EXPIRY);
+    let max_uses: Option<u64> = runtime::get_named_arg(constants::ARG_MAX_USES);
+    let max_spend: Option<U512> = runtime::get_named_arg(constants::ARG_MAX_SPEND);
+    consent::set(
+        caller,
+        contract_hash,
+        entry_points.clone(),
+        expiry,
+        max_uses,
+        max_spend,
+    );
+
+    casper_event_standard::emit(SetConsent::new(
+        caller,
+        contract_hash,
+        entry_points,
+        expiry,
+        max_uses,
+        max_spend,
+    ));
+}
+
+#[no_mangle]
+pub extern "C" fn revoke_consent() {
+    let caller = utils::get_immediate_caller();
+
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    consent::revoke(caller, contract_hash);
+
+    casper_event_standard::emit(RevokeConsent::new(caller, contract_hash));
+}
+
+#[no_mangle]
+pub extern "C" fn set_escrow() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let timeout: Option<u64> = runtime::get_named_arg(constants::ARG_TIMEOUT);
+    escrow::set_timeout(contract_hash, timeout);
+
+    casper_event_standard::emit(SetEscrow::new(contract_hash, timeout));
+}
+
+#[no_mangle]
+pub extern "C" fn confirm_payment() {
+    let request_id: u64 = runtime::get_named_arg(constants::ARG_REQUEST_ID);
+    let (contract_hash, _, _) = escrow::get(request_id);
+    permission::require(Permission::Contract(contract_hash));
+
+    let (_, _, (amount, _)) = escrow::take(request_id);
+    pay_recipient(contract_hash, amount);
+
+    casper_event_standard::emit(ConfirmPayment::new(request_id, contract_hash, amount));
+}
+
+#[no_mangle]
+pub extern "C" fn refund_payment() {
+    let request_id: u64 = runtime::get_named_arg(constants::ARG_REQUEST_ID);
+    let (contract_hash, _, _) = escrow::get(request_id);
+    permission::require(Permission::Contract(contract_hash));
+
+    let (_, caller, (amount, _)) = escrow::take(request_id);
+    credit_wallet(caller, amount);
+
+    casper_event_standard::emit(RefundPayment::new(request_id, caller, amount));
+}
+
+#[no_mangle]
+pub extern "C" fn reclaim_payment() {
+    let request_id: u64 = runtime::get_named_arg(constants::ARG_REQUEST_ID);
+    let (_, caller, (amount, deadline)) = escrow::get(request_id);
+    if utils::get_immediate_caller() != caller {
+        runtime::revert(ApiError::from(Error::Unauthorized))
+    }
+    if u64::from(runtime::get_blocktime()) < deadline {
+        runtime::revert(ApiError::from(Error::EscrowLocked))
+    }
+
+    escrow::take(request_id);
+    credit_wallet(caller, amount);
+
+    casper_event_standard::emit(ReclaimPayment::new(request_id, caller, amount));
+}
+
+#[no_mangle]
+pub extern "C" fn update_relayers() {
+    permission::require(Permission::Installer);
+
+    let add: Vec<AccountHash> = runtime::get_named_arg(constants::ARG_ADD);
+    let remove: Vec<AccountHash> = runtime::get_named_arg(constants::ARG_REMOVE);
+    let relayers = utils::get_uref(constants::KEY_RELAYERS);
+    for relayer in add.iter() {
+        utils::write_storage_dic(relayers, relayer.to_string().as_str(), true);
+    }
+    for relayer in remove.iter() {
+        utils::write_storage_dic(relayers, relayer.to_string().as_str(), false);
+    }
+
+    casper_event_standard::emit(UpdateRelayers::new(&add, &remove));
+}
+
+/// Lets the caller, as an owner, restrict which relayers may spend its balance.
+#[no_mangle]
+pub extern "C" fn set_owner_relayers() {
+    let owner = utils::get_immediate_caller();
+
+    let restricted: bool = runtime::get_named_arg(constants::ARG_RESTRICTED);
+    let add: Vec<AccountHash> = runtime::get_named_arg(constants::ARG_ADD);
+    let remove: Vec<AccountHash> = runtime::get_named_arg(constants::ARG_REMOVE);
+    rules::set_owner_relayers(owner, restricted, &add, &remove);
+
+    casper_event_standard::emit(SetOwnerRelayers::new(owner, restricted, &add, &remove));
+}
+
+#[no_mangle]
+pub extern "C" fn update_denied_callers() {
+    permission::require(Permission::Installer);
+
+    let add: Vec<Key> = runtime::get_named_arg(constants::ARG_ADD);
+    let remove: Vec<Key> = runtime::get_named_arg(constants::ARG_REMOVE);
+    rules::update_denylist(
+        &add.iter().map(utils::key_to_str).collect::<Vec<String>>(),
+        &remove
+            .iter()
+            .map(utils::key_to_str)
+            .collect::<Vec<String>>(),
+    );
+
+    casper_event_standard::emit(UpdateDeniedCallers::new(&add, &remove));
+}
+
+#[no_mangle]
+pub extern "C" fn update_denied_contracts() {
+    permission::require(Permission::Installer);
+
+    let add: Vec<ContractHash> = runtime::get_named_arg(constants::ARG_ADD);
+    let remove: Vec<ContractHash> = runtime::get_named_arg(constants::ARG_REMOVE);
+    rules::update_denylist(
+        &add.iter().map(ToString::to_string).collect::<Vec<String>>(),
+        &remove
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>(),
+    );
+
+    casper_event_standard::emit(UpdateDeniedContracts::new(&add, &remove));
+}
+
+/// Denies contract packages, so calls to a package registration are refused whichever version
+/// they resolve to.
+#[no_mangle]
+pub extern "C" fn update_denied_packages() {
+    permission::require(Permission::Installer);
+
+    let add: Vec<ContractPackageHash> = runtime::get_named_arg(constants::ARG_ADD);
+    let remove: Vec<ContractPackageHash> = runtime::get_named_arg(constants::ARG_REMOVE);
+    rules::update_denylist(
+        &add.iter()
+            .map(|contract_package_hash| contract_package_hash.to_formatted_string())
+            .collect::<Vec<String>>(),
+        &remove
+            .iter()
+            .map(|contract_package_hash| contract_package_hash.to_formatted_string())
+            .collect::<Vec<String>>(),
+    );
+
+    casper_event_standard::emit(UpdateDeniedPackages::new(&add, &remove));
+}
+
+/// Suspends sponsorship of a registered contract, or of all of the caller's contracts when
+/// `contract` is `None`.
+#[no_mangle]
+pub extern "C" fn suspend_sponsorship() {
+    let contract_hash: Option<ContractHash> = runtime::get_named_arg(constants::ARG_CONTRACT);
+    if let Some(contract_hash) = contract_hash {
+        permission::require(Permission::ContractOwner(contract_hash));
+    }
+
+    let owner = utils::get_immediate_caller();
+    rules::set_suspended(owner, contract_hash, true);
+
+    casper_event_standard::emit(SuspendSponsorship::new(owner, contract_hash));
+}
+
+#[no_mangle]
+pub extern "C" fn resume_sponsorship() {
+    let contract_hash: Option<ContractHash> = runtime::get_named_arg(constants::ARG_CONTRACT);
+    if let Some(contract_hash) = contract_hash {
+        permission::require(Permission::ContractOwner(contract_hash));
+    }
+
+    let owner = utils::get_immediate_caller();
+    rules::set_suspended(owner, contract_hash, false);
+
+    casper_event_standard::emit(ResumeSponsorship::new(owner, contract_hash));
+}
+
+#[no_mangle]
+pub extern "C" fn is_suspended() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    let owner = get_owner(contract_hash);
+    runtime::ret(CLValue::from_t(rules::is_suspended(contract_hash, owner)).unwrap_or_revert());
+}
+
+#[no_mangle]
+pub extern "C" fn set_post_hook() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let enabled: bool = runtime::get_named_arg(constants::ARG_ENABLED);
+    let hook: Option<ContractPackageHash> = runtime::get_named_arg(constants::ARG_HOOK);
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_POST_HOOK),
+        contract_hash.to_string().as_str(),
+        (enabled, hook),
+    );
+
+    casper_event_standard::emit(SetPostHook::new(contract_hash, enabled, hook));
+}
+
+/// Grants non-withdrawable gas credits to an owner, funded from the fee purse.
+#[no_mangle]
+pub extern "C" fn grant_credit() {
+    permission::require(Permission::Installer);
+
+    let owner: Key = runtime::get_named_arg(constants::ARG_OWNER);
+    let amount: U512 = runtime::get_named_arg(constants::ARG_AMOUNT);
+    let expiry: Option<u64> = runtime::get_named_arg(constants::ARG_EXPIRY);
+    utils::get_storage_dic::<U512>(
+        utils::get_uref(constants::KEY_OWNER_BALANCE),
+        &utils::key_to_str(&owner),
+    )
+    .unwrap_or_revert_with(ApiError::from(Error::Unregistered));
+    credit::grant(owner, amount, expiry);
+
+    casper_event_standard::emit(GrantCredit::new(owner, amount, expiry));
+}
+
+#[no_mangle]
+pub extern "C" fn deposit() {
+    let owner: Key = runtime::get_named_arg(constants::ARG_OWNER);
+    let owner_balance = utils::get_storage_dic::<U512>(
+        utils::get_uref(constants::KEY_OWNER_BALANCE),
+        &utils::key_to_str(&owner),
+    )
+    .unwrap_or_revert_with(ApiError::from(Error::Unregistered));
+
+    let purse = runtime::get_key(constants::KEY_PURSE)
+        .unwrap_or_revert()
+        .into_uref()
+        .unwrap_or_revert();
+    let purse_balance = system::get_purse_balance(purse).unwrap_or_revert();
+
+    system::transfer_from_purse_to_purse(
+        purse,
+        utils::get_uref(constants::KEY_DEPOSIT_PURSE),
+        purse_balance,
+        None,
+    )
+    .unwrap_or_revert();
+
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_OWNER_BALANCE),
+        &utils::key_to_str(&owner),
+        owner_balance + purse_balance,
     );
 
     casper_event_standard::emit(Deposit::new(owner, purse_balance));
 }
 
-fn install_contract() {
-    let name: String = runtime::get_named_arg(constants::ARG_NAME);
-    if name.is_empty() {
-        runtime::revert(ApiError::from(ApiError::InvalidArgument))
-    }
-    // Create the entry points for this contract.
-    let mut entry_points = EntryPoints::new();
-    load_entry_points(&mut entry_points);
-    let mut named_keys = NamedKeys::new();
-    named_keys.insert(
-        constants::KEY_NAME.to_string(),
-        storage::new_uref(name).into(),
-    );
-    named_keys.insert(
-        constants::KEY_INSTALLER.to_string(),
-        storage::new_uref(runtime::get_caller()).into(),
-    );
-    named_keys.insert(
-        constants::KEY_FEE_RATE.to_string(),
-        storage::new_uref(0u32).into(),
-    );
-    // Create a new contract package
-    let (contract_hash, contract_version) = storage::new_contract(
-        entry_points,
-        Some(named_keys),
-        Some(constants::CONTRACT_PACKAGE_NAME.to_string()),
-        Some(constants::CONTRACT_ACCESS_UREF.to_string()),
-    );
+#[no_mangle]
+pub extern "C" fn deposit_wallet() {
+    // Contract callers fund their wallet by calling from their own package
+    let account = utils::get_immediate_caller();
+    // Each deposit comes in its own purse, so nobody can claim CSPR waiting in the shared one
+    let purse: URef = runtime::get_named_arg(constants::ARG_PURSE);
+    let amount = system::get_purse_balance(purse).unwrap_or_revert();
+    if amount.is_zero() {
+        runtime::revert(ApiError::InvalidArgument)
+    }
+
+    system::transfer_from_purse_to_purse(
+        purse,
+        utils::get_uref(constants::KEY_DEPOSIT_PURSE),
+        amount,
+        None,
+    )
+    .unwrap_or_revert();
+    credit_wallet(account, amount);
+
+    casper_event_standard::emit(WalletDeposit::new(account, amount));
+}
+
+#[no_mangle]
+pub extern "C" fn withdraw_wallet() {
+    let account = utils::get_immediate_caller();
+    let amount: U512 = runtime::get_named_arg(constants::ARG_AMOUNT);
+    // Accounts withdraw to themselves by default, contracts must name a purse
+    let recipient =
+        utils::get_optional_named_arg::<Key>(constants::ARG_RECIPIENT).unwrap_or(account);
+    let balance = utils::get_storage_dic::<U512>(
+        utils::get_uref(constants::KEY_CALLER_BALANCE),
+        &utils::key_to_str(&account),
+    )
+    .unwrap_or_default();
+    if balance < amount {
+        runtime::revert(ApiError::from(Error::InsufficientBalance))
+    }
+
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_CALLER_BALANCE),
+        &utils::key_to_str(&account),
+        balance - amount,
+    );
+    let deposit_purse = utils::get_uref(constants::KEY_DEPOSIT_PURSE);
+    match recipient {
+        Key::Account(recipient) => {
+            system::transfer_from_purse_to_account(deposit_purse, recipient, amount, None)
+                .map(|_| ())
+                .unwrap_or_revert()
+        }
+        Key::URef(purse) => {
+            system::transfer_from_purse_to_purse(deposit_purse, purse, amount, None)
+                .unwrap_or_revert()
+        }
+        _ => runtime::revert(ApiError::InvalidArgument),
+    }
+
+    casper_event_standard::emit(WalletWithdraw::new(account, amount));
+}
+
+/// Returns the context of the relayed call in flight, so its target can read the real caller
+/// back, or `None` outside of a relayed call.
+#[no_mangle]
+pub extern "C" fn current_context() {
+    runtime::ret(CLValue::from_t(context::current()).unwrap_or_revert());
+}
+
+/// Configures which relay context fields are injected into the contract's relayed calls, and under
+/// which argument names.
+#[no_mangle]
+pub extern "C" fn set_injected_args() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let fields: Vec<(u8, String)> = runtime::get_named_arg(constants::ARG_FIELDS);
+    context::set_injected_args(contract_hash, fields.clone());
+
+    casper_event_standard::emit(SetInjectedArgs::new(contract_hash, fields));
+}
+
+#[no_mangle]
+pub extern "C" fn set_subsidy() {
+    let contract_hash: ContractHash = runtime::get_named_arg(constants::ARG_CONTRACT);
+    permission::require(Permission::ContractOwner(contract_hash));
+
+    let allowance: Option<U512> = runtime::get_named_arg(constants::ARG_ALLOWANCE);
+    let period: u64 = runtime::get_named_arg(constants::ARG_PERIOD);
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_SUBSIDY),
+        contract_hash.to_string().as_str(),
+        allowance.map(|allowance| (allowance, period)),
+    );
+
+    casper_event_standard::emit(SetSubsidy::new(contract_hash, allowance, period));
+}
+
+fn install_contract() {
+    let name: String = runtime::get_named_arg(constants::ARG_NAME);
+    if name.is_empty() {
+        runtime::revert(ApiError::InvalidArgument)
+    }
+    // Create the entry points for this contract.
+    let mut entry_points = EntryPoints::new();
+    load_entry_points(&mut entry_points);
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(
+        constants::KEY_NAME.to_string(),
+        storage::new_uref(name).into(),
+    );
+    named_keys.insert(
+        constants::KEY_INSTALLER.to_string(),
+        storage::new_uref(runtime::get_caller()).into(),
+    );
+    named_keys.insert(
+        constants::KEY_FEE_RATE.to_string(),
+        storage::new_uref(0u32).into(),
+    );
+    // Create a new contract package
+    let (contract_hash, contract_version) = storage::new_contract(
+        entry_points,
+        Some(named_keys),
+        Some(constants::CONTRACT_PACKAGE_NAME.to_string()),
+        Some(constants::CONTRACT_ACCESS_UREF.to_string()),
+    );
+
+    // Store the contract version in the context's named keys.
+    runtime::put_key(
+        constants::CONTRACT_VERSION_KEY,
+        storage::new_uref(contract_version).into(),
+    );
+    // Create a named key for the contract hash.
+    runtime::put_key(constants::CONTRACT_KEY, contract_hash.into());
+
+    // Call contract to initialize
+    runtime::call_contract::<()>(contract_hash, constants::ENTRY_POINT_INIT, runtime_args! {});
+}
+
+fn upgrade_contract() {
+    let contract_package_hash = runtime::get_key(constants::CONTRACT_PACKAGE_NAME)
+        .unwrap_or_revert()
+        .into_hash()
+        .unwrap()
+        .into();
+
+    // Create the entry points for this contract.
+    let mut entry_points = EntryPoints::new();
+    load_entry_points(&mut entry_points);
+
+    let (contract_hash, contract_version) =
+        storage::add_contract_version(contract_package_hash, entry_points, NamedKeys::default());
+
+    // Update contract hash and version
+    runtime::put_key(
+        constants::CONTRACT_VERSION_KEY,
+        storage::new_uref(contract_version).into(),
+    );
+    runtime::put_key(constants::CONTRACT_KEY, contract_hash.into());
+
+    // Create the storage added since the previous version
+    runtime::call_contract::<()>(
+        contract_hash,
+        constants::ENTRY_POINT_MIGRATE,
+        runtime_args! {},
+    );
+}
+
+fn load_entry_points(entry_points: &mut EntryPoints) {
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_INIT,
+        Vec::new(),
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_MIGRATE,
+        Vec::new(),
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_RESERVE,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(constants::ARG_ENTRY_POINT, CLType::String),
+            Parameter::new(constants::ARG_CALLER, CLType::Key),
+            Parameter::new(constants::ARG_GAS_AMOUNT, CLType::U512),
+            Parameter::new(constants::ARG_PAY_AMOUNT, CLType::U512),
+            Parameter::new(constants::ARG_ARGS, CLType::List(Box::new(CLType::Any))),
+        ],
+        CLType::U64,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_CALL_RESERVED,
+        vec![Parameter::new(constants::ARG_REQUEST_ID, CLType::U64)],
+        CLType::Any,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SETTLE,
+        vec![
+            Parameter::new(constants::ARG_REQUEST_ID, CLType::U64),
+            Parameter::new(constants::ARG_GAS_USED, CLType::U512),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_EXPIRE_RESERVATION,
+        vec![Parameter::new(constants::ARG_REQUEST_ID, CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_RESERVATION_TIMEOUT,
+        vec![Parameter::new(constants::ARG_TIMEOUT, CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_REGISTER,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(constants::ARG_OWNER, CLType::Key),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_REGISTER_PACKAGE,
+        vec![
+            Parameter::new(
+                constants::ARG_CONTRACT_PACKAGE,
+                ContractPackageHash::cl_type(),
+            ),
+            Parameter::new(constants::ARG_OWNER, CLType::Key),
+            Parameter::new(
+                constants::ARG_VERSION,
+                CLType::Option(Box::new(CLType::U32)),
+            ),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_PACKAGE_VERSION,
+        vec![
+            Parameter::new(
+                constants::ARG_CONTRACT_PACKAGE,
+                ContractPackageHash::cl_type(),
+            ),
+            Parameter::new(
+                constants::ARG_VERSION,
+                CLType::Option(Box::new(CLType::U32)),
+            ),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_DEPOSIT,
+        vec![
+            Parameter::new(constants::ARG_OWNER, CLType::Key),
+            Parameter::new(constants::ARG_AMOUNT, U512::cl_type()),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_CALL_ON_BEHALF_BATCH,
+        vec![
+            Parameter::new(
+                constants::ARG_CALLS,
+                CLType::List(Box::new(<(ContractHash, String, (Bytes, U512))>::cl_type())),
+            ),
+            Parameter::new(constants::ARG_CALLER, CLType::Key),
+            Parameter::new(constants::ARG_GAS_AMOUNT, CLType::U512),
+            Parameter::new(
+                constants::ARG_CEP18_HASH,
+                CLType::Option(Box::new(ContractHash::cl_type())),
+            ),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_CALL_ON_BEHALF,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(constants::ARG_ENTRY_POINT, CLType::String),
+            Parameter::new(constants::ARG_CALLER, CLType::Key),
+            Parameter::new(constants::ARG_GAS_AMOUNT, CLType::U512),
+            Parameter::new(constants::ARG_PAY_AMOUNT, CLType::U512),
+            Parameter::new(constants::ARG_ARGS, CLType::List(Box::new(CLType::Any))),
+            Parameter::new(
+                constants::ARG_CEP18_HASH,
+                CLType::Option(Box::new(ContractHash::cl_type())),
+            ),
+        ],
+        CLType::Any,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        String::from(constants::ENTRY_POINT_GET_PURSE),
+        Vec::new(),
+        CLType::URef,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_FEE_RATE,
+        vec![Parameter::new(constants::ARG_FEE_RATE, CLType::U512)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_CLAIM_FEE,
+        Vec::new(),
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_LOW_BALANCE,
+        vec![
+            Parameter::new(constants::ARG_THRESHOLD, CLType::U512),
+            Parameter::new(constants::ARG_PATH, CLType::List(Box::new(CLType::Key))),
+            Parameter::new(constants::ARG_AMOUNT_IN, CLType::U256),
+            Parameter::new(constants::ARG_AMOUNT_OUT_MIN, CLType::U512),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_TOP_UP,
+        vec![Parameter::new(constants::ARG_OWNER, CLType::Key)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_PAYOUT,
+        vec![
+            Parameter::new(
+                constants::ARG_DESTINATION,
+                CLType::Option(Box::new(CLType::Key)),
+            ),
+            Parameter::new(constants::ARG_ACCUMULATE, CLType::Bool),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_CLAIM_PAYOUT,
+        vec![
+            Parameter::new(
+                constants::ARG_CEP18_HASH,
+                CLType::Option(Box::new(ContractHash::cl_type())),
+            ),
+            Parameter::new(constants::ARG_RECIPIENT, CLType::Key),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_SWAP_ROUTER,
+        vec![Parameter::new(
+            constants::ARG_ROUTER,
+            CLType::Option(Box::new(ContractPackageHash::cl_type())),
+        )],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SWAP_TOKENS,
+        vec![
+            Parameter::new(constants::ARG_CEP18_HASH, ContractHash::cl_type()),
+            Parameter::new(constants::ARG_AMOUNT_IN, CLType::U256),
+            Parameter::new(constants::ARG_AMOUNT_OUT_MIN, CLType::U512),
+            Parameter::new(constants::ARG_PATH, CLType::List(Box::new(CLType::Key))),
+            Parameter::new(constants::ARG_TO_FEE_PURSE, CLType::Bool),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_ENTRY_POINTS,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(constants::ARG_RESTRICTED, CLType::Bool),
+            Parameter::new(
+                constants::ARG_ENTRY_POINTS,
+                Vec::<(String, Option<U512>)>::cl_type(),
+            ),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_CALLER_LIST_MODE,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(constants::ARG_MODE, CLType::U8),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
 
-    // Store the contract version in the context's named keys.
-    runtime::put_key(
-        constants::CONTRACT_VERSION_KEY,
-        storage::new_uref(contract_version).into(),
-    );
-    // Create a named key for the contract hash.
-    runtime::put_key(constants::CONTRACT_KEY, contract_hash.into());
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_UPDATE_CALLER_LIST,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(constants::ARG_DENYLIST, CLType::Bool),
+            Parameter::new(constants::ARG_ADD, Vec::<Key>::cl_type()),
+            Parameter::new(constants::ARG_REMOVE, Vec::<Key>::cl_type()),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
 
-    // Call contract to initialize
-    runtime::call_contract::<()>(contract_hash, constants::ENTRY_POINT_INIT, runtime_args! {});
-}
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_QUOTA,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(constants::ARG_WINDOW, CLType::U64),
+            Parameter::new(constants::ARG_MAX_CALLS, CLType::U64),
+            Parameter::new(constants::ARG_MAX_GAS, CLType::U512),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
 
-fn upgrade_contract() {
-    let contract_package_hash = runtime::get_key(constants::CONTRACT_PACKAGE_NAME)
-        .unwrap_or_revert()
-        .into_hash()
-        .unwrap()
-        .into();
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_BUDGET,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(
+                constants::ARG_AMOUNT,
+                CLType::Option(Box::new(CLType::U512)),
+            ),
+            Parameter::new(constants::ARG_PERIOD, CLType::U64),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
 
-    // Create the entry points for this contract.
-    let mut entry_points = EntryPoints::new();
-    load_entry_points(&mut entry_points);
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_GET_BUDGET,
+        vec![Parameter::new(
+            constants::ARG_CONTRACT,
+            ContractHash::cl_type(),
+        )],
+        CLType::Option(Box::new(CLType::U512)),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
 
-    let (contract_hash, contract_version) =
-        storage::add_contract_version(contract_package_hash, entry_points, NamedKeys::default());
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_POLICY,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(
+                constants::ARG_POLICY,
+                CLType::Option(Box::new(ContractPackageHash::cl_type())),
+            ),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
 
-    // Update contract hash and version
-    runtime::put_key(
-        constants::CONTRACT_VERSION_KEY,
-        storage::new_uref(contract_version).into(),
-    );
-    runtime::put_key(constants::CONTRACT_KEY, contract_hash.into());
-}
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_CONSENT,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(
+                constants::ARG_ENTRY_POINTS,
+                CLType::List(Box::new(CLType::String)),
+            ),
+            Parameter::new(constants::ARG_EXPIRY, CLType::Option(Box::new(CLType::U64))),
+            Parameter::new(
+                constants::ARG_MAX_USES,
+                CLType::Option(Box::new(CLType::U64)),
+            ),
+            Parameter::new(
+                constants::ARG_MAX_SPEND,
+                CLType::Option(Box::new(CLType::U512)),
+            ),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
 
-fn load_entry_points(entry_points: &mut EntryPoints) {
     entry_points.add_entry_point(EntryPoint::new(
-        constants::ENTRY_POINT_INIT,
-        Vec::new(),
+        constants::ENTRY_POINT_REVOKE_CONSENT,
+        vec![Parameter::new(
+            constants::ARG_CONTRACT,
+            ContractHash::cl_type(),
+        )],
         CLType::Unit,
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
 
     entry_points.add_entry_point(EntryPoint::new(
-        constants::ENTRY_POINT_REGISTER,
+        constants::ENTRY_POINT_SET_ESCROW,
         vec![
             Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
-            Parameter::new(constants::ARG_OWNER, AccountHash::cl_type()),
+            Parameter::new(
+                constants::ARG_TIMEOUT,
+                CLType::Option(Box::new(CLType::U64)),
+            ),
         ],
         CLType::Unit,
         EntryPointAccess::Public,
@@ -352,10 +1955,40 @@ fn load_entry_points(entry_points: &mut EntryPoints) {
     ));
 
     entry_points.add_entry_point(EntryPoint::new(
-        constants::ENTRY_POINT_DEPOSIT,
+        constants::ENTRY_POINT_CONFIRM_PAYMENT,
+        vec![Parameter::new(constants::ARG_REQUEST_ID, CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_REFUND_PAYMENT,
+        vec![Parameter::new(constants::ARG_REQUEST_ID, CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_RECLAIM_PAYMENT,
+        vec![Parameter::new(constants::ARG_REQUEST_ID, CLType::U64)],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_UPDATE_RELAYERS,
         vec![
-            Parameter::new(constants::ARG_OWNER, AccountHash::cl_type()),
-            Parameter::new(constants::ARG_AMOUNT, U512::cl_type()),
+            Parameter::new(
+                constants::ARG_ADD,
+                CLType::List(Box::new(AccountHash::cl_type())),
+            ),
+            Parameter::new(
+                constants::ARG_REMOVE,
+                CLType::List(Box::new(AccountHash::cl_type())),
+            ),
         ],
         CLType::Unit,
         EntryPointAccess::Public,
@@ -363,17 +1996,109 @@ fn load_entry_points(entry_points: &mut EntryPoints) {
     ));
 
     entry_points.add_entry_point(EntryPoint::new(
-        constants::ENTRY_POINT_CALL_ON_BEHALF,
+        constants::ENTRY_POINT_SET_OWNER_RELAYERS,
+        vec![
+            Parameter::new(constants::ARG_RESTRICTED, CLType::Bool),
+            Parameter::new(
+                constants::ARG_ADD,
+                CLType::List(Box::new(AccountHash::cl_type())),
+            ),
+            Parameter::new(
+                constants::ARG_REMOVE,
+                CLType::List(Box::new(AccountHash::cl_type())),
+            ),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_UPDATE_DENIED_CALLERS,
+        vec![
+            Parameter::new(constants::ARG_ADD, CLType::List(Box::new(CLType::Key))),
+            Parameter::new(constants::ARG_REMOVE, CLType::List(Box::new(CLType::Key))),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_UPDATE_DENIED_CONTRACTS,
+        vec![
+            Parameter::new(
+                constants::ARG_ADD,
+                CLType::List(Box::new(ContractHash::cl_type())),
+            ),
+            Parameter::new(
+                constants::ARG_REMOVE,
+                CLType::List(Box::new(ContractHash::cl_type())),
+            ),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_UPDATE_DENIED_PACKAGES,
+        vec![
+            Parameter::new(
+                constants::ARG_ADD,
+                CLType::List(Box::new(ContractPackageHash::cl_type())),
+            ),
+            Parameter::new(
+                constants::ARG_REMOVE,
+                CLType::List(Box::new(ContractPackageHash::cl_type())),
+            ),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SUSPEND_SPONSORSHIP,
+        vec![Parameter::new(
+            constants::ARG_CONTRACT,
+            CLType::Option(Box::new(ContractHash::cl_type())),
+        )],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_RESUME_SPONSORSHIP,
+        vec![Parameter::new(
+            constants::ARG_CONTRACT,
+            CLType::Option(Box::new(ContractHash::cl_type())),
+        )],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_IS_SUSPENDED,
+        vec![Parameter::new(
+            constants::ARG_CONTRACT,
+            ContractHash::cl_type(),
+        )],
+        CLType::Bool,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_POST_HOOK,
         vec![
             Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
-            Parameter::new(constants::ARG_ENTRY_POINT, CLType::String),
-            Parameter::new(constants::ARG_CALLER, CLType::Key),
-            Parameter::new(constants::ARG_GAS_AMOUNT, CLType::U512),
-            Parameter::new(constants::ARG_PAY_AMOUNT, CLType::U512),
-            Parameter::new(constants::ARG_ARGS, CLType::List(Box::new(CLType::Any))),
+            Parameter::new(constants::ARG_ENABLED, CLType::Bool),
             Parameter::new(
-                constants::ARG_CEP18_HASH,
-                CLType::Option(Box::new(ContractHash::cl_type())),
+                constants::ARG_HOOK,
+                CLType::Option(Box::new(ContractPackageHash::cl_type())),
             ),
         ],
         CLType::Unit,
@@ -382,24 +2107,68 @@ fn load_entry_points(entry_points: &mut EntryPoints) {
     ));
 
     entry_points.add_entry_point(EntryPoint::new(
-        String::from(constants::ENTRY_POINT_GET_PURSE),
-        Vec::new(),
-        CLType::URef,
+        constants::ENTRY_POINT_GRANT_CREDIT,
+        vec![
+            Parameter::new(constants::ARG_OWNER, CLType::Key),
+            Parameter::new(constants::ARG_AMOUNT, CLType::U512),
+            Parameter::new(constants::ARG_EXPIRY, CLType::Option(Box::new(CLType::U64))),
+        ],
+        CLType::Unit,
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
 
     entry_points.add_entry_point(EntryPoint::new(
-        constants::ENTRY_POINT_SET_FEE_RATE,
-        vec![Parameter::new(constants::ARG_FEE_RATE, CLType::U512)],
+        constants::ENTRY_POINT_DEPOSIT_WALLET,
+        vec![Parameter::new(constants::ARG_PURSE, CLType::URef)],
         CLType::Unit,
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
 
     entry_points.add_entry_point(EntryPoint::new(
-        constants::ENTRY_POINT_CLAIM_FEE,
+        constants::ENTRY_POINT_WITHDRAW_WALLET,
+        vec![
+            Parameter::new(constants::ARG_AMOUNT, CLType::U512),
+            Parameter::new(constants::ARG_RECIPIENT, CLType::Key),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_CURRENT_CONTEXT,
         Vec::new(),
+        CLType::Option(Box::new(StoredContext::cl_type())),
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_INJECTED_ARGS,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(
+                constants::ARG_FIELDS,
+                CLType::List(Box::new(<(u8, String)>::cl_type())),
+            ),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_SET_SUBSIDY,
+        vec![
+            Parameter::new(constants::ARG_CONTRACT, ContractHash::cl_type()),
+            Parameter::new(
+                constants::ARG_ALLOWANCE,
+                CLType::Option(Box::new(CLType::U512)),
+            ),
+            Parameter::new(constants::ARG_PERIOD, CLType::U64),
+        ],
         CLType::Unit,
         EntryPointAccess::Public,
         EntryPointType::Contract,
@@ -0,0 +1,154 @@
+use crate::errors::Error;
+use crate::events::Payout;
+use crate::{constants, utils};
+use alloc::format;
+use alloc::string::{String, ToString};
+use casper_contract::contract_api::{runtime, system};
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+use casper_types::account::AccountHash;
+use casper_types::{runtime_args, ApiError, ContractHash, Key, RuntimeArgs, URef, U512};
+
+/// Purse destinations are kept as named keys so the relay keeps access to them.
+fn purse_key_name(relayer: AccountHash) -> String {
+    format!("{}{}", constants::PREFIX_PAYOUT_PURSE, relayer)
+}
+
+fn get_destination(relayer: AccountHash) -> (Option<Key>, bool) {
+    utils::get_storage_dic::<(Option<Key>, bool)>(
+        utils::get_uref(constants::KEY_PAYOUT),
+        relayer.to_string().as_str(),
+    )
+    .unwrap_or((None, false))
+}
+
+fn get_purse(relayer: AccountHash) -> URef {
+    runtime::get_key(purse_key_name(relayer).as_str())
+        .unwrap_or_revert_with(ApiError::MissingKey)
+        .into_uref()
+        .unwrap_or_revert()
+}
+
+pub(crate) fn set_destination(relayer: AccountHash, destination: Option<Key>, accumulate: bool) {
+    match destination {
+        None | Some(Key::Account(_)) | Some(Key::Hash(_)) => {}
+        Some(Key::URef(purse)) if purse.is_addable() => {
+            runtime::put_key(purse_key_name(relayer).as_str(), purse.into())
+        }
+        _ => runtime::revert(ApiError::InvalidArgument),
+    }
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_PAYOUT),
+        relayer.to_string().as_str(),
+        (destination, accumulate),
+    );
+}
+
+fn credit_ledger(relayer: AccountHash, cep18_hash: Option<ContractHash>, amount: U512) {
+    let ledger = utils::get_uref(constants::KEY_PAYOUT_BALANCE);
+    let key = utils::get_dictionary_key(&(relayer, cep18_hash));
+    let balance = utils::get_storage_dic::<U512>(ledger, &key).unwrap_or_default();
+    utils::write_storage_dic(ledger, &key, balance + amount);
+}
+
+/// Reimburses `relayer` with `amount` of CSPR from the deposit purse. Payouts that can't be sent
+/// to the relayer's destination accumulate in its payout ledger.
+pub(crate) fn pay_cspr(relayer: AccountHash, amount: U512) {
+    let (destination, accumulate) = get_destination(relayer);
+    let deposit_purse = utils::get_uref(constants::KEY_DEPOSIT_PURSE);
+    let recipient = match destination.unwrap_or(Key::from(relayer)) {
+        Key::Account(account) if !accumulate => {
+            system::transfer_from_purse_to_account(deposit_purse, account, amount, None)
+                .unwrap_or_revert();
+            Some(Key::from(account))
+        }
+        Key::URef(_) if !accumulate => {
+            let purse = get_purse(relayer);
+            system::transfer_from_purse_to_purse(deposit_purse, purse, amount, None)
+                .unwrap_or_revert();
+            Some(Key::from(purse.remove_access_rights()))
+        }
+        _ => {
+            system::transfer_from_purse_to_purse(
+                deposit_purse,
+                utils::get_uref(constants::KEY_PAYOUT_PURSE),
+                amount,
+                None,
+            )
+            .unwrap_or_revert();
+            credit_ledger(relayer, None, amount);
+            None
+        }
+    };
+
+    casper_event_standard::emit(Payout::new(relayer, recipient, amount, None));
+}
+
+/// Reimburses `relayer` with `amount` of CEP18 tokens taken from `payer`'s allowance. Payouts that
+/// can't be sent to the relayer's destination are held by the relay in its payout ledger.
+pub(crate) fn pay_cep18(relayer: AccountHash, payer: Key, cep18_hash: ContractHash, amount: U512) {
+    let (destination, accumulate) = get_destination(relayer);
+    let recipient = match destination.unwrap_or(Key::from(relayer)) {
+        destination @ (Key::Account(_) | Key::Hash(_)) if !accumulate => Some(destination),
+        _ => None,
+    };
+
+    let _: () = runtime::call_contract(
+        cep18_hash,
+        constants::ENTRY_POINT_TRANSFER_FROM,
+        runtime_args! {
+            constants::ARG_OWNER => payer,
+            constants::ARG_RECIPIENT => recipient.unwrap_or_else(|| {
+                Key::from(utils::get_contract_package().unwrap_or_revert())
+            }),
+            constants::ARG_AMOUNT => amount,
+        },
+    );
+    if recipient.is_none() {
+        credit_ledger(relayer, Some(cep18_hash), amount);
+    }
+
+    casper_event_standard::emit(Payout::new(relayer, recipient, amount, Some(cep18_hash)));
+}
+
+/// Sends the relayer's whole ledger balance of CSPR or of a CEP18 token to `recipient`.
+pub(crate) fn claim(
+    relayer: AccountHash,
+    cep18_hash: Option<ContractHash>,
+    recipient: Key,
+) -> U512 {
+    let ledger = utils::get_uref(constants::KEY_PAYOUT_BALANCE);
+    let key = utils::get_dictionary_key(&(relayer, cep18_hash));
+    let amount = utils::get_storage_dic::<U512>(ledger, &key).unwrap_or_default();
+    if amount.is_zero() {
+        runtime::revert(ApiError::from(Error::InsufficientBalance))
+    }
+    utils::write_storage_dic(ledger, &key, U512::zero());
+
+    match (cep18_hash, recipient) {
+        (None, Key::Account(account)) => system::transfer_from_purse_to_account(
+            utils::get_uref(constants::KEY_PAYOUT_PURSE),
+            account,
+            amount,
+            None,
+        )
+        .map(|_| ())
+        .unwrap_or_revert(),
+        (None, Key::URef(purse)) => system::transfer_from_purse_to_purse(
+            utils::get_uref(constants::KEY_PAYOUT_PURSE),
+            purse,
+            amount,
+            None,
+        )
+        .unwrap_or_revert(),
+        (Some(cep18_hash), Key::Account(_) | Key::Hash(_)) => runtime::call_contract(
+            cep18_hash,
+            constants::ENTRY_POINT_TRANSFER,
+            runtime_args! {
+                constants::ARG_RECIPIENT => recipient,
+                constants::ARG_AMOUNT => amount,
+            },
+        ),
+        _ => runtime::revert(ApiError::InvalidArgument),
+    }
+    amount
+}
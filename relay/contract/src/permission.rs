@@ -1,10 +1,18 @@
 use crate::errors::Error;
 use crate::{constants, utils};
+use alloc::string::ToString;
 use casper_contract::contract_api::runtime;
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
 use casper_types::account::AccountHash;
+use casper_types::{ContractHash, Key};
 
 pub enum Permission {
     Installer,
+    Relayer,
+    /// The deploy's own account session, so contracts called within the deploy can't act for it.
+    Session,
+    ContractOwner(ContractHash),
+    Contract(ContractHash),
 }
 
 pub(crate) fn require(permission: Permission) {
@@ -16,5 +24,42 @@ pub(crate) fn require(permission: Permission) {
                 runtime::revert(Error::Unauthorized);
             }
         }
+        Permission::Relayer => {
+            let installer = utils::get_storage::<AccountHash>(constants::KEY_INSTALLER);
+            let relayer = utils::get_storage_dic::<bool>(
+                utils::get_uref(constants::KEY_RELAYERS),
+                caller.to_string().as_str(),
+            )
+            .unwrap_or_default();
+            if caller != installer && !relayer {
+                runtime::revert(Error::Unauthorized);
+            }
+        }
+        Permission::Session => {
+            if utils::get_immediate_caller() != Key::from(caller) {
+                runtime::revert(Error::Unauthorized);
+            }
+        }
+        Permission::ContractOwner(contract_hash) => {
+            let owner = utils::get_registered_owner(contract_hash)
+                .unwrap_or_revert_with(Error::Unregistered);
+            // Owners may be contract packages, so check the immediate caller
+            if utils::get_immediate_caller() != owner {
+                runtime::revert(Error::Unauthorized);
+            }
+        }
+        Permission::Contract(contract_hash) => {
+            // Package registrations are keyed by the package hash, contracts by their own hash
+            let registered = match utils::get_immediate_caller_contract() {
+                Some((contract_package_hash, caller_hash)) => {
+                    caller_hash == contract_hash
+                        || utils::package_registration(contract_package_hash) == contract_hash
+                }
+                None => false,
+            };
+            if !registered {
+                runtime::revert(Error::Unauthorized);
+            }
+        }
     }
 }
@@ -0,0 +1,106 @@
+use crate::errors::Error;
+use crate::{constants, utils};
+use alloc::string::{String, ToString};
+use casper_contract::contract_api::runtime;
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+use casper_types::account::AccountHash;
+use casper_types::bytesrepr::Bytes;
+use casper_types::{ContractHash, Key, U512};
+
+/// Target contract, owner and reserving relayer, and the locked gas and fee with the block time the
+/// lock expires at.
+pub(crate) type Reservation = (ContractHash, (Key, AccountHash), (U512, U512, u64));
+
+/// Caller, target contract, and the entry point, serialized arguments and payment of the call.
+pub(crate) type ReservedCall = (Key, ContractHash, (String, Bytes, U512));
+
+/// Locks `gas_amount` plus `fee` out of the owner's balance for the call, and returns the block
+/// time at which the reservation expires.
+pub(crate) fn reserve(
+    request_id: u64,
+    owner: Key,
+    relayer: AccountHash,
+    gas_amount: U512,
+    fee: U512,
+    call: ReservedCall,
+) -> u64 {
+    let balances = utils::get_uref(constants::KEY_OWNER_BALANCE);
+    let balance =
+        utils::get_storage_dic::<U512>(balances, &utils::key_to_str(&owner)).unwrap_or_default();
+    if balance < gas_amount + fee {
+        runtime::revert(Error::InsufficientBalance)
+    }
+    utils::write_storage_dic(
+        balances,
+        &utils::key_to_str(&owner),
+        balance - gas_amount - fee,
+    );
+
+    let now: u64 = runtime::get_blocktime().into();
+    let deadline = now + utils::get_storage::<u64>(constants::KEY_RESERVATION_TIMEOUT);
+    let (_, contract_hash, _) = call;
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_RESERVATION),
+        request_id.to_string().as_str(),
+        Some((contract_hash, (owner, relayer), (gas_amount, fee, deadline))),
+    );
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_RESERVED_CALL),
+        request_id.to_string().as_str(),
+        Some(call),
+    );
+    deadline
+}
+
+pub(crate) fn get(request_id: u64) -> Reservation {
+    utils::get_storage_dic::<Option<Reservation>>(
+        utils::get_uref(constants::KEY_RESERVATION),
+        request_id.to_string().as_str(),
+    )
+    .flatten()
+    .unwrap_or_revert_with(Error::ReservationNotFound)
+}
+
+/// Removes the reserved call so it runs at most once.
+pub(crate) fn take_call(request_id: u64) -> ReservedCall {
+    let calls = utils::get_uref(constants::KEY_RESERVED_CALL);
+    let call = utils::get_storage_dic::<Option<ReservedCall>>(calls, &request_id.to_string())
+        .flatten()
+        .unwrap_or_revert_with(Error::ReservationNotFound);
+    utils::write_storage_dic(calls, &request_id.to_string(), None::<ReservedCall>);
+    call
+}
+
+/// Whether `call_reserved` ran the reserved call. A reverted run leaves the call in place.
+pub(crate) fn is_called(request_id: u64) -> bool {
+    utils::get_storage_dic::<Option<ReservedCall>>(
+        utils::get_uref(constants::KEY_RESERVED_CALL),
+        request_id.to_string().as_str(),
+    )
+    .flatten()
+    .is_none()
+}
+
+/// Removes a settled or expired reservation, along with its call if it never ran.
+pub(crate) fn take(request_id: u64) -> Reservation {
+    let reservation = get(request_id);
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_RESERVATION),
+        request_id.to_string().as_str(),
+        None::<Reservation>,
+    );
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_RESERVED_CALL),
+        request_id.to_string().as_str(),
+        None::<ReservedCall>,
+    );
+    reservation
+}
+
+/// Returns unspent locked funds to the owner's balance.
+pub(crate) fn refund(owner: Key, amount: U512) {
+    let balances = utils::get_uref(constants::KEY_OWNER_BALANCE);
+    let balance =
+        utils::get_storage_dic::<U512>(balances, &utils::key_to_str(&owner)).unwrap_or_default();
+    utils::write_storage_dic(balances, &utils::key_to_str(&owner), balance + amount);
+}
@@ -0,0 +1,421 @@
+use crate::errors::Error;
+use crate::{constants, utils};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use casper_contract::contract_api::runtime;
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+use casper_types::account::AccountHash;
+use casper_types::bytesrepr::{Bytes, ToBytes};
+use casper_types::{
+    runtime_args, ApiError, ContractHash, ContractPackageHash, Key, RuntimeArgs, U512,
+};
+use core::convert::TryFrom;
+
+/// Which caller list of a registered contract `call_on_behalf` enforces.
+#[repr(u8)]
+#[derive(PartialEq, Eq)]
+pub enum CallerListMode {
+    Disabled = 0,
+    Allowlist = 1,
+    Denylist = 2,
+}
+
+impl TryFrom<u8> for CallerListMode {
+    type Error = ApiError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(CallerListMode::Disabled),
+            1 => Ok(CallerListMode::Allowlist),
+            2 => Ok(CallerListMode::Denylist),
+            _ => Err(ApiError::InvalidArgument),
+        }
+    }
+}
+
+/// Reverts unless `entry_point` is sponsored on a contract restricting its entry points and
+/// `gas_amount` is within the entry point's gas cap.
+pub(crate) fn check_entry_point(contract_hash: ContractHash, entry_point: &str, gas_amount: U512) {
+    let restricted = utils::get_storage_dic::<bool>(
+        utils::get_uref(constants::KEY_ENTRY_POINT_RESTRICTED),
+        contract_hash.to_string().as_str(),
+    )
+    .unwrap_or_default();
+    if !restricted {
+        return;
+    }
+
+    let gas_cap = utils::get_storage_dic::<Option<U512>>(
+        utils::get_uref(constants::KEY_ENTRY_POINT_GAS_CAP),
+        &utils::get_dictionary_key(&(contract_hash, entry_point)),
+    )
+    .flatten()
+    .unwrap_or_else(|| runtime::revert(Error::EntryPointNotAllowed));
+    if gas_amount > gas_cap {
+        runtime::revert(Error::GasCapExceeded)
+    }
+}
+
+/// Allows entry points with their gas cap, or disallows them with a `None` cap.
+pub(crate) fn set_entry_points(
+    contract_hash: ContractHash,
+    restricted: bool,
+    entry_points: &[(String, Option<U512>)],
+) {
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_ENTRY_POINT_RESTRICTED),
+        contract_hash.to_string().as_str(),
+        restricted,
+    );
+    let gas_caps = utils::get_uref(constants::KEY_ENTRY_POINT_GAS_CAP);
+    for (entry_point, gas_cap) in entry_points {
+        utils::write_storage_dic(
+            gas_caps,
+            &utils::get_dictionary_key(&(contract_hash, entry_point.as_str())),
+            *gas_cap,
+        );
+    }
+}
+
+fn caller_list_key(mode: &CallerListMode) -> &'static str {
+    match mode {
+        CallerListMode::Denylist => constants::KEY_CALLER_DENYLIST,
+        _ => constants::KEY_CALLER_ALLOWLIST,
+    }
+}
+
+/// Reverts unless `caller` is on the contract's allowlist, or is off its denylist.
+pub(crate) fn check_caller(contract_hash: ContractHash, caller: Key) {
+    let mode = utils::get_storage_dic::<u8>(
+        utils::get_uref(constants::KEY_CALLER_LIST_MODE),
+        contract_hash.to_string().as_str(),
+    )
+    .unwrap_or_default();
+    let mode = CallerListMode::try_from(mode).unwrap_or(CallerListMode::Disabled);
+    if mode == CallerListMode::Disabled {
+        return;
+    }
+
+    let listed = utils::get_storage_dic::<bool>(
+        utils::get_uref(caller_list_key(&mode)),
+        &utils::get_dictionary_key(&(contract_hash, caller)),
+    )
+    .unwrap_or_default();
+    if listed == (mode == CallerListMode::Denylist) {
+        runtime::revert(Error::CallerNotAllowed)
+    }
+}
+
+pub(crate) fn set_caller_list_mode(contract_hash: ContractHash, mode: CallerListMode) {
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_CALLER_LIST_MODE),
+        contract_hash.to_string().as_str(),
+        mode as u8,
+    );
+}
+
+/// Adds and removes callers on the contract's allowlist or denylist.
+pub(crate) fn update_caller_list(
+    contract_hash: ContractHash,
+    mode: CallerListMode,
+    add: &[Key],
+    remove: &[Key],
+) {
+    let list = utils::get_uref(caller_list_key(&mode));
+    for caller in add {
+        utils::write_storage_dic(
+            list,
+            &utils::get_dictionary_key(&(contract_hash, *caller)),
+            true,
+        );
+    }
+    for caller in remove {
+        utils::write_storage_dic(
+            list,
+            &utils::get_dictionary_key(&(contract_hash, *caller)),
+            false,
+        );
+    }
+}
+
+/// Counts a call of `gas_amount` against the caller's quota on the contract, reverting once the
+/// calls or gas in the last window would exceed it.
+///
+/// The window slides, approximated with two buckets aligned on multiples of the window: usage in
+/// the current bucket counts fully, and usage in the previous one in proportion to how much of it
+/// still overlaps the last window. This keeps a single fixed-size entry per caller instead of a
+/// log of calls, at the cost of assuming the previous bucket's calls were evenly spread.
+pub(crate) fn use_quota(contract_hash: ContractHash, caller: Key, gas_amount: U512) {
+    let (window, max_calls, max_gas) = utils::get_storage_dic::<(u64, u64, U512)>(
+        utils::get_uref(constants::KEY_QUOTA),
+        contract_hash.to_string().as_str(),
+    )
+    .unwrap_or_default();
+    if window == 0 {
+        return;
+    }
+
+    let usage_uref = utils::get_uref(constants::KEY_QUOTA_USAGE);
+    let usage_key = utils::get_dictionary_key(&(contract_hash, caller));
+    let now: u64 = runtime::get_blocktime().into();
+    let bucket_start = now - now % window;
+    let (start, (mut previous_calls, mut previous_gas), (mut calls, mut gas)) =
+        utils::get_storage_dic::<(u64, (u64, U512), (u64, U512))>(usage_uref, &usage_key)
+            .unwrap_or((bucket_start, (0, U512::zero()), (0, U512::zero())));
+    if start != bucket_start {
+        // The current bucket becomes the previous one, unless a whole window went by unused
+        if start.saturating_add(window) == bucket_start {
+            previous_calls = calls;
+            previous_gas = gas;
+        } else {
+            previous_calls = 0;
+            previous_gas = U512::zero();
+        }
+        calls = 0;
+        gas = U512::zero();
+    }
+
+    calls += 1;
+    gas += gas_amount;
+    let overlap = window - (now - bucket_start);
+    let window_calls = U512::from(calls) + U512::from(previous_calls) * overlap / window;
+    let window_gas = gas + previous_gas * overlap / window;
+    // A zero limit leaves that dimension unlimited
+    if (max_calls > 0 && window_calls > U512::from(max_calls))
+        || (!max_gas.is_zero() && window_gas > max_gas)
+    {
+        runtime::revert(Error::QuotaExceeded)
+    }
+    utils::write_storage_dic(
+        usage_uref,
+        &usage_key,
+        (bucket_start, (previous_calls, previous_gas), (calls, gas)),
+    );
+}
+
+/// Returns the contract's budget, how much of it is spent and when its period started, with the
+/// spent amount reset once the period elapsed.
+fn get_budget(contract_hash: ContractHash) -> Option<(U512, U512, u64)> {
+    let (amount, period) = utils::get_storage_dic::<Option<(U512, u64)>>(
+        utils::get_uref(constants::KEY_BUDGET),
+        contract_hash.to_string().as_str(),
+    )
+    .flatten()?;
+    let now: u64 = runtime::get_blocktime().into();
+    let (spent, period_start) = utils::get_storage_dic::<(U512, u64)>(
+        utils::get_uref(constants::KEY_BUDGET_USAGE),
+        contract_hash.to_string().as_str(),
+    )
+    .unwrap_or((U512::zero(), now));
+    // A zero period means a fixed budget that never resets
+    if period > 0 && now >= period_start.saturating_add(period) {
+        return Some((amount, U512::zero(), now));
+    }
+    Some((amount, spent, period_start))
+}
+
+/// Returns what is left of the contract's budget, or `None` if it has no budget.
+pub(crate) fn remaining_budget(contract_hash: ContractHash) -> Option<U512> {
+    get_budget(contract_hash).map(|(amount, spent, _)| amount.saturating_sub(spent))
+}
+
+/// Spends `gas_amount` sponsored by the owner from the contract's budget, reverting if it would
+/// overrun it.
+pub(crate) fn use_budget(contract_hash: ContractHash, gas_amount: U512) {
+    let (amount, spent, period_start) = match get_budget(contract_hash) {
+        Some(budget) => budget,
+        None => return,
+    };
+    if spent + gas_amount > amount {
+        runtime::revert(Error::BudgetExceeded)
+    }
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_BUDGET_USAGE),
+        contract_hash.to_string().as_str(),
+        (spent + gas_amount, period_start),
+    );
+}
+
+/// Gives back budget spent on gas a reservation didn't use.
+pub(crate) fn release_budget(contract_hash: ContractHash, gas_amount: U512) {
+    let (_, spent, period_start) = match get_budget(contract_hash) {
+        Some(budget) => budget,
+        None => return,
+    };
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_BUDGET_USAGE),
+        contract_hash.to_string().as_str(),
+        (spent.saturating_sub(gas_amount), period_start),
+    );
+}
+
+/// Asks the policy contract attached to the registered contract, if any, whether to sponsor the
+/// call. `args` are passed serialized so the policy can inspect them.
+///
+/// The host has no way to cap the gas of a nested call, so the policy runs on whatever is left of
+/// the relayer's deploy payment. A policy that runs out of gas fails every relayed call of the
+/// contract until the owner detaches it.
+pub(crate) fn check_policy(
+    contract_hash: ContractHash,
+    caller: Key,
+    entry_point: &str,
+    args: &RuntimeArgs,
+    gas_amount: U512,
+) {
+    let policy = match utils::get_storage_dic::<Option<ContractPackageHash>>(
+        utils::get_uref(constants::KEY_POLICY),
+        contract_hash.to_string().as_str(),
+    )
+    .flatten()
+    {
+        Some(policy) => policy,
+        None => return,
+    };
+
+    let approved: bool = runtime::call_versioned_contract(
+        policy,
+        None,
+        constants::ENTRY_POINT_APPROVE_SPONSORSHIP,
+        runtime_args! {
+            constants::ARG_CALLER => caller,
+            constants::ARG_ENTRY_POINT => entry_point,
+            constants::ARG_ARGS => Bytes::from(args.to_bytes().unwrap_or_revert()),
+            constants::ARG_GAS_AMOUNT => gas_amount,
+        },
+    );
+    if !approved {
+        runtime::revert(Error::SponsorshipDenied)
+    }
+}
+
+/// Calls `post_relay` after the relayed call, on the hook contract or else on the registered
+/// contract itself, if enabled, so it can settle with the caller or the owner.
+pub(crate) fn call_post_hook(
+    contract_hash: ContractHash,
+    caller: Key,
+    owner: Key,
+    entry_point: &str,
+    gas_amount: U512,
+    fee: U512,
+) {
+    let (enabled, hook) = utils::get_storage_dic::<(bool, Option<ContractPackageHash>)>(
+        utils::get_uref(constants::KEY_POST_HOOK),
+        contract_hash.to_string().as_str(),
+    )
+    .unwrap_or_default();
+    if !enabled {
+        return;
+    }
+
+    let args = runtime_args! {
+        constants::ARG_CALLER => caller,
+        constants::ARG_OWNER => owner,
+        constants::ARG_ENTRY_POINT => entry_point,
+        constants::ARG_GAS_AMOUNT => gas_amount,
+        constants::ARG_FEE => fee,
+    };
+    match hook {
+        Some(hook) => runtime::call_versioned_contract::<()>(
+            hook,
+            None,
+            constants::ENTRY_POINT_POST_RELAY,
+            args,
+        ),
+        None => {
+            utils::call_registered::<()>(contract_hash, constants::ENTRY_POINT_POST_RELAY, args)
+        }
+    }
+}
+
+/// Suspends or resumes sponsorship of one registered contract, or of all of `owner`'s contracts
+/// when `contract_hash` is `None`.
+pub(crate) fn set_suspended(owner: Key, contract_hash: Option<ContractHash>, suspended: bool) {
+    let key = match contract_hash {
+        Some(contract_hash) => contract_hash.to_string(),
+        None => utils::key_to_str(&owner),
+    };
+    utils::write_storage_dic(utils::get_uref(constants::KEY_SUSPENDED), &key, suspended);
+}
+
+pub(crate) fn is_suspended(contract_hash: ContractHash, owner: Key) -> bool {
+    let suspended = utils::get_uref(constants::KEY_SUSPENDED);
+    [contract_hash.to_string(), utils::key_to_str(&owner)]
+        .iter()
+        .any(|key| utils::get_storage_dic::<bool>(suspended, key).unwrap_or_default())
+}
+
+pub(crate) fn check_suspended(contract_hash: ContractHash, owner: Key) {
+    if is_suspended(contract_hash, owner) {
+        runtime::revert(Error::SponsorshipSuspended)
+    }
+}
+
+/// Adds and removes callers or target contracts, by dictionary item key, on the relay-wide
+/// denylist.
+pub(crate) fn update_denylist(add: &[String], remove: &[String]) {
+    let denylist = utils::get_uref(constants::KEY_DENYLIST);
+    for entry in add {
+        utils::write_storage_dic(denylist, entry, true);
+    }
+    for entry in remove {
+        utils::write_storage_dic(denylist, entry, false);
+    }
+}
+
+/// Reverts if the contract or the caller is on the relay-wide denylist, whatever the owner's
+/// settings are. A package registration is checked under both its registration key and its
+/// package hash, since the host doesn't expose which contract a package version resolves to.
+pub(crate) fn check_denylist(contract_hash: ContractHash, account: Key) {
+    let denylist = utils::get_uref(constants::KEY_DENYLIST);
+    let mut keys = vec![contract_hash.to_string(), utils::key_to_str(&account)];
+    if utils::is_package_registration(contract_hash) {
+        keys.push(ContractPackageHash::new(contract_hash.value()).to_formatted_string());
+    }
+    if keys
+        .iter()
+        .any(|key| utils::get_storage_dic::<bool>(denylist, key).unwrap_or_default())
+    {
+        runtime::revert(Error::Denied)
+    }
+}
+
+/// Restricts which relayers may spend `owner`'s balance and updates the owner's relayer list.
+pub(crate) fn set_owner_relayers(
+    owner: Key,
+    restricted: bool,
+    add: &[AccountHash],
+    remove: &[AccountHash],
+) {
+    utils::write_storage_dic(
+        utils::get_uref(constants::KEY_OWNER_RELAYER_RESTRICTED),
+        &utils::key_to_str(&owner),
+        restricted,
+    );
+    let list = utils::get_uref(constants::KEY_OWNER_RELAYERS);
+    for relayer in add {
+        utils::write_storage_dic(list, &utils::get_dictionary_key(&(owner, *relayer)), true);
+    }
+    for relayer in remove {
+        utils::write_storage_dic(list, &utils::get_dictionary_key(&(owner, *relayer)), false);
+    }
+}
+
+/// Reverts if `owner` restricts its relayers and `relayer` isn't on its list.
+pub(crate) fn check_owner_relayer(owner: Key, relayer: AccountHash) {
+    let restricted = utils::get_storage_dic::<bool>(
+        utils::get_uref(constants::KEY_OWNER_RELAYER_RESTRICTED),
+        &utils::key_to_str(&owner),
+    )
+    .unwrap_or_default();
+    if !restricted {
+        return;
+    }
+    let allowed = utils::get_storage_dic::<bool>(
+        utils::get_uref(constants::KEY_OWNER_RELAYERS),
+        &utils::get_dictionary_key(&(owner, relayer)),
+    )
+    .unwrap_or_default();
+    if !allowed {
+        runtime::revert(Error::RelayerNotAllowed)
+    }
+}
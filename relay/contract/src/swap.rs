@@ -0,0 +1,67 @@
+use crate::errors::Error;
+use crate::{constants, utils};
+use alloc::vec::Vec;
+use casper_contract::contract_api::{runtime, system};
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+use casper_types::{
+    runtime_args, ApiError, ContractHash, ContractPackageHash, Key, RuntimeArgs, URef, U256, U512,
+};
+
+/// Returns the configured DEX router, if any.
+pub(crate) fn get_router() -> Option<ContractPackageHash> {
+    utils::get_storage::<Option<ContractPackageHash>>(constants::KEY_SWAP_ROUTER)
+}
+
+/// Pulls `amount_in` tokens from `owner`'s allowance and swaps them into CSPR through the router.
+/// Returns a new purse holding the proceeds and their amount.
+pub(crate) fn swap_to_cspr(
+    owner: Key,
+    cep18_hash: ContractHash,
+    amount_in: U256,
+    amount_out_min: U512,
+    path: Vec<Key>,
+) -> (URef, U512) {
+    let router = get_router().unwrap_or_revert_with(ApiError::from(Error::SwapRouterNotSet));
+
+    // Pull the tokens and let the router spend them
+    let contract_package = utils::get_contract_package().unwrap_or_revert();
+    let _: () = runtime::call_contract(
+        cep18_hash,
+        constants::ENTRY_POINT_TRANSFER_FROM,
+        runtime_args! {
+            constants::ARG_OWNER => owner,
+            constants::ARG_RECIPIENT => Key::from(contract_package),
+            constants::ARG_AMOUNT => amount_in,
+        },
+    );
+    let _: () = runtime::call_contract(
+        cep18_hash,
+        constants::ENTRY_POINT_APPROVE,
+        runtime_args! {
+            constants::ARG_SPENDER => Key::from(router),
+            constants::ARG_AMOUNT => amount_in,
+        },
+    );
+
+    let purse = system::create_purse();
+    let deadline: u64 = runtime::get_blocktime().into();
+    let _: U512 = runtime::call_versioned_contract(
+        router,
+        None,
+        constants::ENTRY_POINT_SWAP_EXACT_TOKENS_FOR_CSPR,
+        runtime_args! {
+            constants::ARG_AMOUNT_IN => amount_in,
+            constants::ARG_AMOUNT_OUT_MIN => amount_out_min,
+            constants::ARG_PATH => path,
+            constants::ARG_TO => purse.into_add(),
+            constants::ARG_DEADLINE => deadline,
+        },
+    );
+
+    // Check the proceeds ourselves rather than trusting the router
+    let amount_out = system::get_purse_balance(purse).unwrap_or_revert();
+    if amount_out < amount_out_min {
+        runtime::revert(ApiError::from(Error::SlippageExceeded))
+    }
+    (purse, amount_out)
+}
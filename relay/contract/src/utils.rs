@@ -1,9 +1,17 @@
+use crate::constants;
 use alloc::borrow::ToOwned;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
 use casper_contract::contract_api::storage;
 use casper_contract::{contract_api::runtime, ext_ffi, unwrap_or_revert::UnwrapOrRevert};
-use casper_types::bytesrepr::{FromBytes, ToBytes};
+use casper_types::account::AccountHash;
+use casper_types::bytesrepr::{self, FromBytes, ToBytes};
 use casper_types::system::CallStackElement;
-use casper_types::{api_error, ApiError, CLTyped, ContractPackageHash, URef};
+use casper_types::{
+    api_error, ApiError, CLType, CLTyped, CLValue, ContractHash, ContractPackageHash, Key,
+    RuntimeArgs, URef,
+};
 
 #[inline]
 pub(crate) fn get_uref(key: &str) -> URef {
@@ -35,6 +43,129 @@ pub(crate) fn write_storage_dic<T: CLTyped + FromBytes + ToBytes>(dic: URef, key
     storage::dictionary_put(dic, key, value);
 }
 
+/// Builds a dictionary item key from any serializable value, e.g. a `(ContractHash, AccountHash)`
+/// tuple, by hashing it so it fits the dictionary key length limit.
+#[inline]
+pub(crate) fn get_dictionary_key<T: ToBytes>(value: &T) -> String {
+    let bytes = value.to_bytes().unwrap_or_revert();
+    base16::encode_lower(&runtime::blake2b(bytes))
+}
+
+/// Returns the account or contract package that called the current entry point.
+pub(crate) fn get_immediate_caller() -> Key {
+    match *runtime::get_call_stack()
+        .iter()
+        .nth_back(1)
+        .to_owned()
+        .unwrap_or_revert()
+    {
+        CallStackElement::StoredContract {
+            contract_package_hash,
+            ..
+        } => Key::from(contract_package_hash),
+        CallStackElement::Session { account_hash }
+        | CallStackElement::StoredSession { account_hash, .. } => Key::from(account_hash),
+    }
+}
+
+/// Returns the package and hash of the contract that called the current entry point, `None` when
+/// called from a session.
+pub(crate) fn get_immediate_caller_contract() -> Option<(ContractPackageHash, ContractHash)> {
+    match *runtime::get_call_stack()
+        .iter()
+        .nth_back(1)
+        .to_owned()
+        .unwrap_or_revert()
+    {
+        CallStackElement::StoredContract {
+            contract_package_hash,
+            contract_hash,
+        } => Some((contract_package_hash, contract_hash)),
+        _ => None,
+    }
+}
+
+/// Builds the dictionary item key of an account or contract package caller. Accounts keep their
+/// bare account hash so balances recorded per account stay valid.
+pub(crate) fn key_to_str(key: &Key) -> String {
+    match key {
+        Key::Account(account_hash) => account_hash.to_string(),
+        Key::Hash(hash) => ContractPackageHash::new(*hash).to_formatted_string(),
+        _ => runtime::revert(ApiError::InvalidArgument),
+    }
+}
+
+/// Returns the owner of a registered contract. Registrations made before owners were keys hold a
+/// bare account hash, which is read back as an account key.
+pub(crate) fn get_registered_owner(contract_hash: ContractHash) -> Option<Key> {
+    let registrations = get_uref(constants::KEY_REGISTERED_CONTRACT);
+    let key = contract_hash.to_string();
+    get_storage_dic::<Key>(registrations, &key)
+        .or_else(|| get_storage_dic::<AccountHash>(registrations, &key).map(Key::from))
+}
+
+/// Package registrations share the contract registration settings, keyed by the package hash.
+pub(crate) fn package_registration(contract_package_hash: ContractPackageHash) -> ContractHash {
+    ContractHash::new(contract_package_hash.value())
+}
+
+/// Whether the registration was made by package hash, which always sets a version entry.
+pub(crate) fn is_package_registration(contract_hash: ContractHash) -> bool {
+    get_storage_dic::<Option<u32>>(
+        get_uref(constants::KEY_PACKAGE_VERSION),
+        contract_hash.to_string().as_str(),
+    )
+    .is_some()
+}
+
+/// Calls a registered contract, through its package at the latest or the pinned version when it
+/// was registered by package hash.
+pub(crate) fn call_registered<T: CLTyped + FromBytes>(
+    contract_hash: ContractHash,
+    entry_point: &str,
+    args: RuntimeArgs,
+) -> T {
+    let version = get_storage_dic::<Option<u32>>(
+        get_uref(constants::KEY_PACKAGE_VERSION),
+        contract_hash.to_string().as_str(),
+    );
+    match version {
+        Some(version) => runtime::call_versioned_contract(
+            ContractPackageHash::new(contract_hash.value()),
+            version,
+            entry_point,
+            args,
+        ),
+        None => runtime::call_contract(contract_hash, entry_point, args),
+    }
+}
+
+/// Serialized value returned by a called entry point, whatever its type, since the host only
+/// hands back the value's bytes.
+pub(crate) struct RawValue(Vec<u8>);
+
+impl RawValue {
+    pub(crate) fn hash(&self) -> String {
+        base16::encode_lower(&runtime::blake2b(&self.0))
+    }
+
+    pub(crate) fn into_cl_value(self) -> CLValue {
+        CLValue::from_components(CLType::Any, self.0)
+    }
+}
+
+impl CLTyped for RawValue {
+    fn cl_type() -> CLType {
+        CLType::Any
+    }
+}
+
+impl FromBytes for RawValue {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        Ok((RawValue(bytes.to_vec()), &[]))
+    }
+}
+
 pub fn get_named_arg_size(name: &str) -> Option<usize> {
     let mut arg_size: usize = 0;
     let ret = unsafe {
@@ -71,3 +202,17 @@ pub(crate) fn get_contract_package() -> Option<ContractPackageHash> {
         _ => None,
     }
 }
+
+/// Overwrites the event schemas of an upgraded relay. The event standard builds them against its
+/// own `casper-types` version, so they are written as serialized CLValue bytes.
+pub(crate) fn write_event_schemas(uref: URef, schemas: casper_event_standard::Schemas) {
+    use casper_event_standard::casper_types as ces_types;
+    let value = ces_types::CLValue::from_t(schemas)
+        .ok()
+        .and_then(|value| ces_types::bytesrepr::ToBytes::into_bytes(value).ok())
+        .unwrap_or_revert();
+    let key = Key::from(uref).into_bytes().unwrap_or_revert();
+    unsafe {
+        ext_ffi::casper_write(key.as_ptr(), key.len(), value.as_ptr(), value.len());
+    }
+}
@@ -0,0 +1,149 @@
+#![no_std]
+#![no_main]
+
+#[cfg(not(target_arch = "wasm32"))]
+compile_error!("target arch should be wasm32: compile with '--target wasm32-unknown-unknown'");
+
+// We need to explicitly import the std alloc crate and `alloc::string::String` as we're in a
+// `no_std` environment.
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use casper_contract::contract_api::{account, runtime, storage, system};
+use casper_contract::unwrap_or_revert::UnwrapOrRevert;
+use casper_types::contracts::NamedKeys;
+use casper_types::system::CallStackElement;
+use casper_types::{
+    runtime_args, ApiError, CLType, CLValue, ContractHash, ContractPackageHash, EntryPoint,
+    EntryPointAccess, EntryPointType, EntryPoints, Key, Parameter, RuntimeArgs, URef, U256, U512,
+};
+use num_traits::AsPrimitive;
+
+const CONTRACT_PACKAGE_NAME: &str = "router_package_name";
+const CONTRACT_KEY: &str = "router_hash";
+const CONTRACT_VERSION_KEY: &str = "router_version";
+const KEY_PURSE: &str = "purse";
+const KEY_RATE: &str = "rate";
+const ENTRY_POINT_SWAP_EXACT_TOKENS_FOR_CSPR: &str = "swap_exact_tokens_for_cspr";
+const ENTRY_POINT_TRANSFER_FROM: &str = "transfer_from";
+const ARG_AMOUNT: &str = "amount";
+const ARG_RATE: &str = "rate";
+const ARG_AMOUNT_IN: &str = "amount_in";
+const ARG_AMOUNT_OUT_MIN: &str = "amount_out_min";
+const ARG_PATH: &str = "path";
+const ARG_TO: &str = "to";
+const ARG_DEADLINE: &str = "deadline";
+const ARG_OWNER: &str = "owner";
+const ARG_RECIPIENT: &str = "recipient";
+
+/// Mock Uniswap-V2-style router which swaps the first token of `path` into CSPR at a fixed rate
+/// (motes per 1000 token units), paid out of the router's own purse.
+#[no_mangle]
+pub extern "C" fn swap_exact_tokens_for_cspr() {
+    let amount_in: U256 = runtime::get_named_arg(ARG_AMOUNT_IN);
+    let amount_out_min: U512 = runtime::get_named_arg(ARG_AMOUNT_OUT_MIN);
+    let path: Vec<Key> = runtime::get_named_arg(ARG_PATH);
+    let to: URef = runtime::get_named_arg(ARG_TO);
+    let deadline: u64 = runtime::get_named_arg(ARG_DEADLINE);
+
+    if u64::from(runtime::get_blocktime()) > deadline {
+        runtime::revert(ApiError::User(1))
+    }
+
+    let token: ContractHash = path
+        .first()
+        .and_then(|token| token.into_hash())
+        .unwrap_or_revert_with(ApiError::InvalidArgument)
+        .into();
+    let (spender, sender) = get_call_stack_packages();
+    let _: () = runtime::call_contract(
+        token,
+        ENTRY_POINT_TRANSFER_FROM,
+        runtime_args! {
+            ARG_OWNER => Key::from(sender),
+            ARG_RECIPIENT => Key::from(spender),
+            ARG_AMOUNT => amount_in,
+        },
+    );
+
+    let rate: U512 = storage::read(get_uref(KEY_RATE))
+        .unwrap_or_revert()
+        .unwrap_or_revert();
+    let amount_in: U512 = amount_in.as_();
+    let amount_out = amount_in * rate / U512::from(1000);
+    if amount_out < amount_out_min {
+        runtime::revert(ApiError::User(2))
+    }
+
+    system::transfer_from_purse_to_purse(get_uref(KEY_PURSE), to, amount_out, None)
+        .unwrap_or_revert();
+    runtime::ret(CLValue::from_t(amount_out).unwrap_or_revert())
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let rate: U512 = runtime::get_named_arg(ARG_RATE);
+    let amount: U512 = runtime::get_named_arg(ARG_AMOUNT);
+
+    // Fund the router's liquidity from the installer
+    let purse = system::create_purse();
+    system::transfer_from_purse_to_purse(account::get_main_purse(), purse, amount, None)
+        .unwrap_or_revert();
+
+    let mut named_keys = NamedKeys::new();
+    named_keys.insert(KEY_PURSE.to_string(), purse.into());
+    named_keys.insert(KEY_RATE.to_string(), storage::new_uref(rate).into());
+
+    let mut entry_points = EntryPoints::new();
+    entry_points.add_entry_point(EntryPoint::new(
+        ENTRY_POINT_SWAP_EXACT_TOKENS_FOR_CSPR,
+        vec![
+            Parameter::new(ARG_AMOUNT_IN, CLType::U256),
+            Parameter::new(ARG_AMOUNT_OUT_MIN, CLType::U512),
+            Parameter::new(ARG_PATH, CLType::List(Box::new(CLType::Key))),
+            Parameter::new(ARG_TO, CLType::URef),
+            Parameter::new(ARG_DEADLINE, CLType::U64),
+        ],
+        CLType::U512,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
+
+    let (contract_hash, contract_version) = storage::new_contract(
+        entry_points,
+        Some(named_keys),
+        Some(CONTRACT_PACKAGE_NAME.to_string()),
+        None,
+    );
+
+    runtime::put_key(
+        CONTRACT_VERSION_KEY,
+        storage::new_uref(contract_version).into(),
+    );
+    runtime::put_key(CONTRACT_KEY, contract_hash.into());
+}
+
+fn get_uref(key: &str) -> URef {
+    runtime::get_key(key)
+        .unwrap_or_revert_with(ApiError::MissingKey)
+        .into_uref()
+        .unwrap_or_revert()
+}
+
+/// Returns the router's own package and the package of the contract calling it.
+fn get_call_stack_packages() -> (ContractPackageHash, ContractPackageHash) {
+    let call_stack = runtime::get_call_stack();
+    let mut packages = call_stack.iter().rev().map(|element| match element {
+        CallStackElement::StoredContract {
+            contract_package_hash,
+            ..
+        } => *contract_package_hash,
+        _ => runtime::revert(ApiError::InvalidArgument),
+    });
+    let router = packages.next().unwrap_or_revert();
+    let caller = packages.next().unwrap_or_revert();
+    (router, caller)
+}
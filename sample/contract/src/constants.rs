@@ -4,6 +4,13 @@ pub const CONTRACT_VERSION_KEY: &str = "sample_version";
 pub const KEY_MESSAGE: &str = "message";
 pub const KEY_CALLER: &str = "caller";
 pub const KEY_RELAY_CONTRACT_PACKAGE: &str = "relay_contract_package";
+pub const KEY_RELAY_FEE: &str = "relay_fee";
 pub const ENTRY_POINT_SET_MESSAGE: &str = "set_message";
+pub const ENTRY_POINT_CURRENT_CONTEXT: &str = "current_context";
+pub const ENTRY_POINT_POST_RELAY: &str = "post_relay";
 pub const ARG_MESSAGE: &str = "message";
 pub const ARG_CALLER: &str = "caller";
+pub const ARG_OWNER: &str = "owner";
+pub const ARG_ENTRY_POINT: &str = "entry_point";
+pub const ARG_GAS_AMOUNT: &str = "gas_amount";
+pub const ARG_FEE: &str = "fee";
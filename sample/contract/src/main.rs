@@ -14,19 +14,29 @@ use alloc::string::{String, ToString};
 use alloc::vec;
 
 use casper_contract::contract_api::{runtime, storage};
-use casper_types::account::AccountHash;
 use casper_types::contracts::NamedKeys;
 use casper_types::{
-    CLType, ContractPackageHash, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints,
-    Parameter,
+    ApiError, CLType, ContractPackageHash, EntryPoint, EntryPointAccess, EntryPointType,
+    EntryPoints, Key, Parameter, U512,
 };
 
 #[no_mangle]
 pub extern "C" fn set_message() {
-    let caller: AccountHash = utils::get_caller();
+    let caller: Key = utils::get_caller();
     let message: String = runtime::get_named_arg(constants::ARG_MESSAGE);
     utils::write_storage(constants::KEY_MESSAGE, message);
-    utils::write_storage(constants::KEY_CALLER, caller.to_string());
+    utils::write_storage(constants::KEY_CALLER, caller.to_formatted_string());
+}
+
+/// Post-relay hook, called by the relay after each relayed call once enabled with
+/// `set_post_hook`. Records the fee the relay charged for the last call.
+#[no_mangle]
+pub extern "C" fn post_relay() {
+    if !utils::is_called_by_relay() {
+        runtime::revert(ApiError::PermissionDenied)
+    }
+    let fee: U512 = runtime::get_named_arg(constants::ARG_FEE);
+    utils::write_storage(constants::KEY_RELAY_FEE, fee);
 }
 
 #[no_mangle]
@@ -46,6 +56,10 @@ pub extern "C" fn call() {
         constants::KEY_CALLER.to_string(),
         storage::new_uref(String::new()).into(),
     );
+    named_keys.insert(
+        constants::KEY_RELAY_FEE.to_string(),
+        storage::new_uref(U512::zero()).into(),
+    );
     // Create the entry points for this contract.
     let mut entry_points = EntryPoints::new();
     entry_points.add_entry_point(EntryPoint::new(
@@ -55,6 +69,19 @@ pub extern "C" fn call() {
         EntryPointAccess::Public,
         EntryPointType::Contract,
     ));
+    entry_points.add_entry_point(EntryPoint::new(
+        constants::ENTRY_POINT_POST_RELAY,
+        vec![
+            Parameter::new(constants::ARG_CALLER, CLType::Key),
+            Parameter::new(constants::ARG_OWNER, CLType::Key),
+            Parameter::new(constants::ARG_ENTRY_POINT, CLType::String),
+            Parameter::new(constants::ARG_GAS_AMOUNT, CLType::U512),
+            Parameter::new(constants::ARG_FEE, CLType::U512),
+        ],
+        CLType::Unit,
+        EntryPointAccess::Public,
+        EntryPointType::Contract,
+    ));
 
     // Create a new contract package
     let (contract_hash, contract_version) = storage::new_contract(
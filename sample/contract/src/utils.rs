@@ -5,7 +5,7 @@ use casper_contract::{contract_api::runtime, unwrap_or_revert::UnwrapOrRevert};
 use casper_types::account::AccountHash;
 use casper_types::bytesrepr::{FromBytes, ToBytes};
 use casper_types::system::CallStackElement;
-use casper_types::{ApiError, CLTyped, ContractPackageHash, URef};
+use casper_types::{runtime_args, ApiError, CLTyped, ContractPackageHash, Key, RuntimeArgs, URef};
 
 #[inline]
 pub(crate) fn get_uref(key: &str) -> URef {
@@ -28,7 +28,7 @@ pub(crate) fn get_storage<T: CLTyped + FromBytes>(name: &str) -> T {
 }
 
 #[inline]
-pub(crate) fn get_caller() -> AccountHash {
+pub(crate) fn get_caller() -> Key {
     match *runtime::get_call_stack()
         .iter()
         .nth_back(1)
@@ -39,14 +39,36 @@ pub(crate) fn get_caller() -> AccountHash {
             contract_package_hash,
             ..
         } => {
-            // Check if called from relay contract
-            if contract_package_hash
-                == get_storage::<ContractPackageHash>(constants::KEY_RELAY_CONTRACT_PACKAGE)
-            {
-                return runtime::get_named_arg::<AccountHash>(constants::ARG_CALLER);
+            // Check if called from relay contract, and read the real caller back from it
+            let relay_contract_package =
+                get_storage::<ContractPackageHash>(constants::KEY_RELAY_CONTRACT_PACKAGE);
+            if contract_package_hash == relay_contract_package {
+                let context: Option<(Key, Key, (AccountHash, u64))> =
+                    runtime::call_versioned_contract(
+                        relay_contract_package,
+                        None,
+                        constants::ENTRY_POINT_CURRENT_CONTEXT,
+                        runtime_args! {},
+                    );
+                let (caller, _, _) = context.unwrap_or_revert();
+                return caller;
             }
-            runtime::get_caller()
+            Key::from(contract_package_hash)
+        }
+        _ => Key::from(runtime::get_caller()),
+    }
+}
+
+/// Returns whether the immediate caller is the relay contract package.
+pub(crate) fn is_called_by_relay() -> bool {
+    match runtime::get_call_stack().iter().nth_back(1) {
+        Some(CallStackElement::StoredContract {
+            contract_package_hash,
+            ..
+        }) => {
+            *contract_package_hash
+                == get_storage::<ContractPackageHash>(constants::KEY_RELAY_CONTRACT_PACKAGE)
         }
-        _ => runtime::get_caller(),
+        _ => false,
     }
 }
@@ -1,16 +1,47 @@
 import 'dotenv/config'
 import {
-  CasperClient, CasperServiceByJsonRPC, CLValueBuilder, Contracts,
-  Keys, PurseIdentifier,
+  CasperClient, CasperServiceByJsonRPC, CLAccountHash, CLByteArray, CLKey, CLList, CLTypeBuilder,
+  CLU32, CLU8, CLURef, CLValue, CLValueBuilder, Contracts, DeployUtil, Keys, PurseIdentifier,
   RuntimeArgs
 } from "casper-js-sdk";
+import {BigNumber} from "@ethersproject/bignumber";
+import {None, Option, Some} from "ts-results";
 import {getAccountInfo, getAccountNamedKeyValue, getBinary, getDeploy} from "./utils";
 import {strict as assert} from 'assert';
 import {CEP18Client, ContractWASM} from "casper-cep18-js-client";
+import {blake2b} from "@noble/hashes/blake2b";
 
 const {Contract} = Contracts;
 const MOTE_RATE = 1_000_000_000;
 
+// User error codes of the relay, as in relay/contract/src/errors.rs
+enum RelayError {
+  Unauthorized = 1,
+  Unregistered = 2,
+  InsufficientBalance = 3,
+  InsufficientAmount = 4,
+  SwapRouterNotSet = 5,
+  SlippageExceeded = 6,
+  EntryPointNotAllowed = 7,
+  GasCapExceeded = 8,
+  CallerNotAllowed = 9,
+  QuotaExceeded = 10,
+  BudgetExceeded = 11,
+  SponsorshipDenied = 12,
+  SponsorshipSuspended = 13,
+  Denied = 14,
+  RelayerNotAllowed = 15,
+  ConsentMissing = 16,
+  EscrowNotFound = 17,
+  EscrowLocked = 18,
+  ReservationNotFound = 19,
+  ReservationExpired = 20,
+  ReservationActive = 21,
+  ReservationNotAllowed = 22,
+  BatchOwnerMismatch = 23,
+  SpendLimitExceeded = 24,
+}
+
 function delay(time: number) {
   return new Promise(resolve => setTimeout(resolve, time));
 }
@@ -36,6 +67,31 @@ const USER2_KEYS = Keys.Ed25519.parseKeyFiles(
   await increaseAllowance();
   await testRelay();
   await testDirect();
+  await testSubsidy();
+  await testSwap();
+  await testPayout();
+  await testTopUp();
+  await testCredit();
+  await testEntryPoints();
+  await testCallerList();
+  await testQuota();
+  await testBudget();
+  await testPolicy();
+  await testPostHook();
+  await testSuspend();
+  await testDenylist();
+  await testRelayers();
+  await testConsent();
+  await testResult();
+  await testBatch();
+  await testPackage();
+  await testInjectedArgs();
+  await testContext();
+  await testCallerKey();
+  await testRegistration();
+  await testWallet();
+  await testEscrow();
+  await testReservation();
 })();
 
 async function setup() {
@@ -113,7 +169,7 @@ async function setup() {
   contractClient.setContractHash(relayContractHash);
   const registerDeploy = contractClient.callEntrypoint("register", RuntimeArgs.fromMap({
     "contract": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleHash.slice(5))),
-    "owner": CLValueBuilder.byteArray(USER1_KEYS.publicKey.toAccountHash()),
+    "owner": CLValueBuilder.key(new CLAccountHash(USER1_KEYS.publicKey.toAccountHash())),
   }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]);
   console.log("*** Register contract ***")
   await delay(500);
@@ -128,6 +184,18 @@ async function setup() {
   const setFeeRateHash = await setFeeRateDeploy.send(process.env.NODE_URL!);
   await getDeploy(process.env.NODE_URL!, setFeeRateHash);
 
+  console.log("*** Consent to relayed calls ***");
+  const setConsentDeploy = contractClient.callEntrypoint("set_consent", RuntimeArgs.fromMap({
+    "contract": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleHash.slice(5))),
+    "entry_points": CLValueBuilder.list([CLValueBuilder.string("set_message")]),
+    "expiry": CLValueBuilder.option(None, CLTypeBuilder.u64()),
+    "max_uses": CLValueBuilder.option(None, CLTypeBuilder.u64()),
+    "max_spend": CLValueBuilder.option(None, CLTypeBuilder.u512()),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]);
+  await delay(500);
+  const setConsentHash = await setConsentDeploy.send(process.env.NODE_URL!);
+  await getDeploy(process.env.NODE_URL!, setConsentHash);
+
   const cep18ContractHash = await getAccountNamedKeyValue(accountInfo, "cep18_contract_hash_USDT");
   console.log("CEP18 contract hash: ", cep18ContractHash)
   cep18.setContractHash(cep18ContractHash);
@@ -152,7 +220,7 @@ async function deposit() {
   contractClient.setContractHash(relayContractHash);
   const depositDeploy = contractClient.install(getBinary('./contracts/deposit.wasm'), RuntimeArgs.fromMap({
     "relay_contract": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(relayContractHash.slice(5))),
-    "owner": CLValueBuilder.byteArray(USER1_KEYS.publicKey.toAccountHash()),
+    "owner": CLValueBuilder.key(new CLAccountHash(USER1_KEYS.publicKey.toAccountHash())),
     "amount": CLValueBuilder.u512(100 * MOTE_RATE),
   }), String(10 * MOTE_RATE), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, [USER1_KEYS])
 
@@ -210,7 +278,7 @@ async function testRelay() {
   const setMessageDeploy = contractClient.callEntrypoint("call_on_behalf", RuntimeArgs.fromMap({
     "contract": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5))),
     "entry_point": CLValueBuilder.string("set_message"),
-    "caller": CLValueBuilder.byteArray(USER1_KEYS.publicKey.toAccountHash()),
+    "caller": CLValueBuilder.key(new CLAccountHash(USER1_KEYS.publicKey.toAccountHash())),
     "gas_amount": CLValueBuilder.u512(10 * MOTE_RATE),
     "pay_amount": CLValueBuilder.u512(0),
     "args": CLValueBuilder.byteArray(RuntimeArgs.fromMap({
@@ -225,7 +293,7 @@ async function testRelay() {
 
   contractClient.setContractHash(sampleContractHash)
   const caller = await contractClient.queryContractData(["caller"]);
-  assert.equal(caller, USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(caller, USER1_KEYS.publicKey.toAccountHashStr());
   const feePurseBalance = await getFeePurseBalance(casperClient, relayContractHash);
   console.log("Fee purse balance: ", feePurseBalance.div(MOTE_RATE / 100).toNumber() / 100);
 
@@ -240,7 +308,7 @@ async function testRelay() {
   const setMessageCep18Deploy = contractClient.callEntrypoint("call_on_behalf", RuntimeArgs.fromMap({
     "contract": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5))),
     "entry_point": CLValueBuilder.string("set_message"),
-    "caller": CLValueBuilder.byteArray(USER1_KEYS.publicKey.toAccountHash()),
+    "caller": CLValueBuilder.key(new CLAccountHash(USER1_KEYS.publicKey.toAccountHash())),
     "gas_amount": CLValueBuilder.u512(MOTE_RATE),
     "pay_amount": CLValueBuilder.u512(0),
     "cep18_hash": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(cep18Hash.slice(5))),
@@ -278,7 +346,1084 @@ async function testDirect() {
   await getDeploy(process.env.NODE_URL!, setMessage);
 
   const caller = await contractClient.queryContractData(["caller"]);
-  assert.equal(caller, USER2_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(caller, USER2_KEYS.publicKey.toAccountHashStr());
+}
+
+async function testSubsidy() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  // The caller pays from its own relay wallet once the subsidy is used up
+  await depositWallet(USER1_KEYS, 10 * MOTE_RATE);
+
+  console.log('*** Set subsidy ***');
+  await sendDeploy(contractClient.callEntrypoint("set_subsidy", RuntimeArgs.fromMap({
+    "contract": sample,
+    "allowance": CLValueBuilder.option(Some(CLValueBuilder.u512(MOTE_RATE))),
+    "period": CLValueBuilder.u64(0),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+
+  // The owner covers the allowance, the caller's wallet the rest, each with its fee
+  const ownerBefore = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  const callerBefore = await getDictionaryBalance(contractClient, "caller_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  console.log('*** Set message with subsidy ***');
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Hello with subsidy", 3 * MOTE_RATE));
+  const ownerAfter = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  const callerAfter = await getDictionaryBalance(contractClient, "caller_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(ownerAfter.toString(), ownerBefore.sub(1_020_000_000).toString());
+  assert.equal(callerAfter.toString(), callerBefore.sub(2_040_000_000).toString());
+  const event = await getCallOnBehalfEvent(contractClient);
+  assert.equal(event.ownerAmount, String(MOTE_RATE));
+  assert.equal(event.callerAmount, String(2 * MOTE_RATE));
+
+  // Reservations are fully sponsored, so they are refused while a subsidy is set
+  console.log('*** Reserve with subsidy ***');
+  await rejectsWith(setMessageDeploy(contractClient, sample, "Never sent", MOTE_RATE, {entryPoint: "reserve"}),
+    userError(RelayError.ReservationNotAllowed));
+
+  await sendDeploy(contractClient.callEntrypoint("set_subsidy", RuntimeArgs.fromMap({
+    "contract": sample,
+    "allowance": CLValueBuilder.option(None, CLTypeBuilder.u512()),
+    "period": CLValueBuilder.u64(0),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+}
+
+async function testSwap() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  let accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const relayContractPackageHash = await getAccountNamedKeyValue(accountInfo, "relay_package_name");
+  const cep18Hash = await getAccountNamedKeyValue(accountInfo, "cep18_contract_hash_USDT");
+  const cep18 = new CEP18Client(process.env.NODE_URL!, process.env.CHAIN_NAME!);
+  cep18.setContractHash(cep18Hash);
+
+  // The mock router pays 1 mote per token unit out of its own liquidity
+  console.log("*** Deploy router ***")
+  await sendDeploy(contractClient.install(
+    getBinary('./contracts/router.wasm'),
+    RuntimeArgs.fromMap({
+      "rate": CLValueBuilder.u512(1000),
+      "amount": CLValueBuilder.u512(50 * MOTE_RATE),
+    }),
+    String(100 * MOTE_RATE),
+    FAUCET_KEYS.publicKey,
+    process.env.NETWORK_NAME!,
+    [FAUCET_KEYS]
+  ));
+  accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const routerPackageHash = await getAccountNamedKeyValue(accountInfo, "router_package_name");
+  console.log("Router contract package hash: ", routerPackageHash)
+
+  contractClient.setContractHash(relayContractHash)
+  console.log('*** Set swap router ***');
+  await sendDeploy(contractClient.callEntrypoint("set_swap_router", RuntimeArgs.fromMap({
+    "router": CLValueBuilder.option(Some(CLValueBuilder.byteArray(Contracts.contractHashToByteArray(routerPackageHash.slice(5))))),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+
+  // The relay pulls the tokens from the relayer's allowance before swapping them
+  await sendDeploy(cep18.approve({
+    spender: CLValueBuilder.byteArray(Contracts.contractHashToByteArray(relayContractPackageHash.slice(5))),
+    amount: MOTE_RATE
+  }, 5_000_000_000, FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, [FAUCET_KEYS]));
+
+  const feePurseBefore = await getFeePurseBalance(casperClient, relayContractHash);
+  const tokensBefore = await cep18.balanceOf(FAUCET_KEYS.publicKey);
+  console.log('*** Swap tokens into the fee purse ***');
+  await sendDeploy(contractClient.callEntrypoint("swap_tokens", RuntimeArgs.fromMap({
+    "cep18_hash": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(cep18Hash.slice(5))),
+    "amount_in": CLValueBuilder.u256(MOTE_RATE),
+    "amount_out_min": CLValueBuilder.u512(MOTE_RATE),
+    "path": CLValueBuilder.list([
+      CLValueBuilder.key(CLValueBuilder.byteArray(Contracts.contractHashToByteArray(cep18Hash.slice(5)))),
+    ]),
+    "to_fee_purse": CLValueBuilder.bool(true),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(20 * MOTE_RATE), [FAUCET_KEYS]));
+  const feePurseAfter = await getFeePurseBalance(casperClient, relayContractHash);
+  const tokensAfter = await cep18.balanceOf(FAUCET_KEYS.publicKey);
+  assert.equal(feePurseAfter.toString(), feePurseBefore.add(MOTE_RATE).toString());
+  assert.equal(tokensAfter.toString(), tokensBefore.sub(MOTE_RATE).toString());
+}
+
+async function testPayout() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  // Ledger entries are keyed by the hash of (relayer, cep18_hash), here with no token
+  const ledgerKey = Buffer.from(blake2b(
+    Uint8Array.from([...FAUCET_KEYS.publicKey.toAccountHash(), 0]), {dkLen: 32}
+  )).toString('hex');
+
+  console.log('*** Accumulate payouts ***');
+  await sendDeploy(contractClient.callEntrypoint("set_payout", RuntimeArgs.fromMap({
+    "destination": CLValueBuilder.option(None, CLTypeBuilder.key()),
+    "accumulate": CLValueBuilder.bool(true),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+
+  const ledgerBefore = await getDictionaryBalance(contractClient, "payout_balance", ledgerKey);
+  console.log('*** Set message with accumulated payout ***');
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Hello with payout", MOTE_RATE));
+  const ledgerAccumulated = await getDictionaryBalance(contractClient, "payout_balance", ledgerKey);
+  assert.equal(ledgerAccumulated.toString(), ledgerBefore.add(MOTE_RATE).toString());
+
+  console.log('*** Claim payout ***');
+  await sendDeploy(contractClient.callEntrypoint("claim_payout", RuntimeArgs.fromMap({
+    "cep18_hash": CLValueBuilder.option(None, CLTypeBuilder.byteArray(32)),
+    "recipient": accountKey(FAUCET_KEYS),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+  const ledgerClaimed = await getDictionaryBalance(contractClient, "payout_balance", ledgerKey);
+  assert.equal(ledgerClaimed.toString(), "0");
+
+  await sendDeploy(contractClient.callEntrypoint("set_payout", RuntimeArgs.fromMap({
+    "destination": CLValueBuilder.option(None, CLTypeBuilder.key()),
+    "accumulate": CLValueBuilder.bool(false),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+}
+
+async function testTopUp() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const cep18Hash = await getAccountNamedKeyValue(accountInfo, "cep18_contract_hash_USDT");
+  contractClient.setContractHash(relayContractHash)
+
+  // A threshold above the balance makes it low, topped up with 1 token through the router
+  console.log('*** Set low balance ***');
+  await sendDeploy(contractClient.callEntrypoint("set_low_balance", RuntimeArgs.fromMap({
+    "threshold": CLValueBuilder.u512(1000 * MOTE_RATE),
+    "path": CLValueBuilder.list([
+      CLValueBuilder.key(CLValueBuilder.byteArray(Contracts.contractHashToByteArray(cep18Hash.slice(5)))),
+    ]),
+    "amount_in": CLValueBuilder.u256(MOTE_RATE),
+    "amount_out_min": CLValueBuilder.u512(MOTE_RATE),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+
+  const topUpDeploy = (keys: Keys.AsymmetricKey) => contractClient.callEntrypoint("top_up", RuntimeArgs.fromMap({
+    "owner": accountKey(USER1_KEYS),
+  }), keys.publicKey, process.env.NETWORK_NAME!, String(20 * MOTE_RATE), [keys]);
+
+  // Only the owner or a relayer picks when the owner's tokens are swapped
+  console.log('*** Top up as another account ***');
+  await rejectsWith(topUpDeploy(USER2_KEYS), userError(RelayError.Unauthorized));
+
+  const balanceBefore = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  console.log('*** Top up ***');
+  await sendDeploy(topUpDeploy(USER1_KEYS));
+  const balanceAfter = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceAfter.toString(), balanceBefore.add(MOTE_RATE).toString());
+
+  await sendDeploy(contractClient.callEntrypoint("set_low_balance", RuntimeArgs.fromMap({
+    "threshold": CLValueBuilder.u512(0),
+    "path": new CLList(CLTypeBuilder.key()),
+    "amount_in": CLValueBuilder.u256(0),
+    "amount_out_min": CLValueBuilder.u512(0),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+}
+
+async function testCredit() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  // Credits are funded from the fee purse, enough for one call of 0.05 CSPR with its fee
+  console.log('*** Grant credit ***');
+  await sendDeploy(contractClient.callEntrypoint("grant_credit", RuntimeArgs.fromMap({
+    "owner": accountKey(USER1_KEYS),
+    "amount": CLValueBuilder.u512(51_000_000),
+    "expiry": CLValueBuilder.option(None, CLTypeBuilder.u64()),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+
+  // Credits are spent before the owner's balance
+  const balanceBefore = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  console.log('*** Set message with credit ***');
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Hello with credit", 50_000_000));
+  const balanceCredited = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceCredited.toString(), balanceBefore.toString());
+
+  console.log('*** Set message once credit is spent ***');
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Hello without credit", 50_000_000));
+  const balanceAfter = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceAfter.toString(), balanceBefore.sub(51_000_000).toString());
+}
+
+async function testEntryPoints() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  const setEntryPoints = (restricted: boolean, gasCap: number | undefined) =>
+    contractClient.callEntrypoint("set_entry_points", RuntimeArgs.fromMap({
+      "contract": sample,
+      "restricted": CLValueBuilder.bool(restricted),
+      "entry_points": CLValueBuilder.list([CLValueBuilder.tuple2([
+        CLValueBuilder.string("set_message"),
+        gasCap === undefined
+          ? CLValueBuilder.option(None, CLTypeBuilder.u512())
+          : CLValueBuilder.option(Some(CLValueBuilder.u512(gasCap))),
+      ])]),
+    }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]);
+
+  console.log('*** Allow set_message up to 2 CSPR ***');
+  await sendDeploy(setEntryPoints(true, 2 * MOTE_RATE));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "Over the cap", 3 * MOTE_RATE),
+    userError(RelayError.GasCapExceeded));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Within the cap", MOTE_RATE));
+
+  console.log('*** Disallow set_message ***');
+  await sendDeploy(setEntryPoints(true, undefined));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "Not sponsored", MOTE_RATE),
+    userError(RelayError.EntryPointNotAllowed));
+
+  await sendDeploy(setEntryPoints(false, undefined));
+}
+
+async function testCallerList() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  const setCallerListMode = (mode: number) => contractClient.callEntrypoint("set_caller_list_mode", RuntimeArgs.fromMap({
+    "contract": sample,
+    "mode": CLValueBuilder.u8(mode),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]);
+  const addCaller = (denylist: boolean) => contractClient.callEntrypoint("update_caller_list", RuntimeArgs.fromMap({
+    "contract": sample,
+    "denylist": CLValueBuilder.bool(denylist),
+    "add": CLValueBuilder.list([accountKey(USER1_KEYS)]),
+    "remove": new CLList(CLTypeBuilder.key()),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]);
+
+  console.log('*** Allowlist ***');
+  await sendDeploy(setCallerListMode(1));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "Not on the allowlist", MOTE_RATE),
+    userError(RelayError.CallerNotAllowed));
+  await sendDeploy(addCaller(false));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "On the allowlist", MOTE_RATE));
+
+  console.log('*** Denylist ***');
+  await sendDeploy(setCallerListMode(2));
+  await sendDeploy(addCaller(true));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "On the denylist", MOTE_RATE),
+    userError(RelayError.CallerNotAllowed));
+
+  await sendDeploy(setCallerListMode(0));
+}
+
+async function testQuota() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  const setQuota = (window: number, maxCalls: number) => contractClient.callEntrypoint("set_quota", RuntimeArgs.fromMap({
+    "contract": sample,
+    "window": CLValueBuilder.u64(window),
+    "max_calls": CLValueBuilder.u64(maxCalls),
+    "max_gas": CLValueBuilder.u512(0),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]);
+
+  // One call per caller and hour
+  console.log('*** Set quota ***');
+  await sendDeploy(setQuota(3_600_000, 1));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Within the quota", MOTE_RATE));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "Over the quota", MOTE_RATE),
+    userError(RelayError.QuotaExceeded));
+
+  await sendDeploy(setQuota(0, 0));
+}
+
+async function testBudget() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  // A fixed budget of 1.5 CSPR fits one call of 1 CSPR
+  console.log('*** Set budget ***');
+  await sendDeploy(contractClient.callEntrypoint("set_budget", RuntimeArgs.fromMap({
+    "contract": sample,
+    "amount": CLValueBuilder.option(Some(CLValueBuilder.u512(1_500_000_000))),
+    "period": CLValueBuilder.u64(0),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Within the budget", MOTE_RATE));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "Over the budget", MOTE_RATE),
+    userError(RelayError.BudgetExceeded));
+
+  await sendDeploy(contractClient.callEntrypoint("set_budget", RuntimeArgs.fromMap({
+    "contract": sample,
+    "amount": CLValueBuilder.option(None, CLTypeBuilder.u512()),
+    "period": CLValueBuilder.u64(0),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+}
+
+async function testPolicy() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  let accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  // The reference policy approves calls of up to 2 CSPR, whatever tokens the caller holds
+  console.log("*** Deploy policy ***")
+  await sendDeploy(contractClient.install(
+    getBinary('./contracts/policy.wasm'),
+    RuntimeArgs.fromMap({
+      "token": CLValueBuilder.option(None, CLTypeBuilder.byteArray(32)),
+      "min_balance": CLValueBuilder.u256(0),
+      "max_gas": CLValueBuilder.u512(2 * MOTE_RATE),
+    }),
+    String(100 * MOTE_RATE),
+    FAUCET_KEYS.publicKey,
+    process.env.NETWORK_NAME!,
+    [FAUCET_KEYS]
+  ));
+  accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const policyPackageHash = await getAccountNamedKeyValue(accountInfo, "policy_package_name");
+  console.log("Policy contract package hash: ", policyPackageHash)
+
+  contractClient.setContractHash(relayContractHash)
+  console.log('*** Set policy ***');
+  await sendDeploy(contractClient.callEntrypoint("set_policy", RuntimeArgs.fromMap({
+    "contract": sample,
+    "policy": CLValueBuilder.option(Some(CLValueBuilder.byteArray(Contracts.contractHashToByteArray(policyPackageHash.slice(5))))),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "Denied by policy", 3 * MOTE_RATE),
+    userError(RelayError.SponsorshipDenied));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Approved by policy", MOTE_RATE));
+
+  await sendDeploy(contractClient.callEntrypoint("set_policy", RuntimeArgs.fromMap({
+    "contract": sample,
+    "policy": CLValueBuilder.option(None, CLTypeBuilder.byteArray(32)),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+}
+
+async function testPostHook() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  const setPostHook = (enabled: boolean) => contractClient.callEntrypoint("set_post_hook", RuntimeArgs.fromMap({
+    "contract": sample,
+    "enabled": CLValueBuilder.bool(enabled),
+    "hook": CLValueBuilder.option(None, CLTypeBuilder.byteArray(32)),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]);
+
+  // Without a hook contract the sample's own post_relay is called, and records the fee
+  console.log('*** Set post hook ***');
+  await sendDeploy(setPostHook(true));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Hello with post hook", MOTE_RATE));
+  contractClient.setContractHash(sampleContractHash)
+  const relayFee = await contractClient.queryContractData(["relay_fee"]);
+  assert.equal(relayFee.toString(), "20000000");
+
+  // Only the relay reports relayed calls to the sample
+  console.log('*** Call post_relay directly ***');
+  await assert.rejects(sendDeploy(contractClient.callEntrypoint("post_relay", RuntimeArgs.fromMap({
+    "caller": accountKey(USER2_KEYS),
+    "owner": accountKey(USER1_KEYS),
+    "entry_point": CLValueBuilder.string("set_message"),
+    "gas_amount": CLValueBuilder.u512(0),
+    "fee": CLValueBuilder.u512(0),
+  }), USER2_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER2_KEYS])));
+
+  contractClient.setContractHash(relayContractHash)
+  await sendDeploy(setPostHook(false));
+}
+
+async function testSuspend() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  const setSuspended = (suspended: boolean, contract: CLByteArray | undefined) =>
+    contractClient.callEntrypoint(suspended ? "suspend_sponsorship" : "resume_sponsorship", RuntimeArgs.fromMap({
+      "contract": contract === undefined
+        ? CLValueBuilder.option(None, CLTypeBuilder.byteArray(32))
+        : CLValueBuilder.option(Some(contract)),
+    }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]);
+
+  console.log('*** Suspend the sample ***');
+  await sendDeploy(setSuspended(true, sample));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "While suspended", MOTE_RATE),
+    userError(RelayError.SponsorshipSuspended));
+  await sendDeploy(setSuspended(false, sample));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Resumed", MOTE_RATE));
+
+  console.log('*** Suspend all contracts of the owner ***');
+  await sendDeploy(setSuspended(true, undefined));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "While the owner is suspended", MOTE_RATE),
+    userError(RelayError.SponsorshipSuspended));
+  await sendDeploy(setSuspended(false, undefined));
+}
+
+async function testDenylist() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  console.log('*** Deny caller ***');
+  const updateDeniedCallers = (add: CLValue, remove: CLValue) =>
+    contractClient.callEntrypoint("update_denied_callers", RuntimeArgs.fromMap({
+      "add": add,
+      "remove": remove,
+    }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]);
+  await sendDeploy(updateDeniedCallers(CLValueBuilder.list([accountKey(USER1_KEYS)]), new CLList(CLTypeBuilder.key())));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "From a denied caller", MOTE_RATE),
+    userError(RelayError.Denied));
+  await sendDeploy(updateDeniedCallers(new CLList(CLTypeBuilder.key()), CLValueBuilder.list([accountKey(USER1_KEYS)])));
+
+  console.log('*** Deny contract ***');
+  const updateDeniedContracts = (add: CLValue, remove: CLValue) =>
+    contractClient.callEntrypoint("update_denied_contracts", RuntimeArgs.fromMap({
+      "add": add,
+      "remove": remove,
+    }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]);
+  await sendDeploy(updateDeniedContracts(CLValueBuilder.list([sample]), new CLList(CLTypeBuilder.byteArray(32))));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "To a denied contract", MOTE_RATE),
+    userError(RelayError.Denied));
+  await sendDeploy(updateDeniedContracts(new CLList(CLTypeBuilder.byteArray(32)), CLValueBuilder.list([sample])));
+
+  await sendDeploy(setMessageDeploy(contractClient, sample, "No longer denied", MOTE_RATE));
+}
+
+async function testRelayers() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+  const user2 = CLValueBuilder.byteArray(USER2_KEYS.publicKey.toAccountHash());
+
+  // Only the installer and approved relayers may relay
+  console.log('*** Relay from an unapproved account ***');
+  await rejectsWith(setMessageDeploy(contractClient, sample, "From an unapproved relayer", MOTE_RATE, {relayer: USER2_KEYS}),
+    userError(RelayError.Unauthorized));
+
+  console.log('*** Approve relayer ***');
+  await sendDeploy(contractClient.callEntrypoint("update_relayers", RuntimeArgs.fromMap({
+    "add": CLValueBuilder.list([user2]),
+    "remove": new CLList(CLTypeBuilder.byteArray(32)),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "From an approved relayer", MOTE_RATE, {relayer: USER2_KEYS}));
+
+  // Owners may further restrict which relayers spend their balance
+  console.log('*** Restrict owner relayers ***');
+  await sendDeploy(contractClient.callEntrypoint("set_owner_relayers", RuntimeArgs.fromMap({
+    "restricted": CLValueBuilder.bool(true),
+    "add": CLValueBuilder.list([CLValueBuilder.byteArray(FAUCET_KEYS.publicKey.toAccountHash())]),
+    "remove": new CLList(CLTypeBuilder.byteArray(32)),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "From a relayer of another owner", MOTE_RATE, {relayer: USER2_KEYS}),
+    userError(RelayError.RelayerNotAllowed));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "From a relayer of the owner", MOTE_RATE));
+
+  await sendDeploy(contractClient.callEntrypoint("set_owner_relayers", RuntimeArgs.fromMap({
+    "restricted": CLValueBuilder.bool(false),
+    "add": new CLList(CLTypeBuilder.byteArray(32)),
+    "remove": new CLList(CLTypeBuilder.byteArray(32)),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+  await sendDeploy(contractClient.callEntrypoint("update_relayers", RuntimeArgs.fromMap({
+    "add": new CLList(CLTypeBuilder.byteArray(32)),
+    "remove": CLValueBuilder.list([user2]),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+}
+
+async function testConsent() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  const setConsent = (entryPoints: string[], maxSpend: number | undefined) =>
+    contractClient.callEntrypoint("set_consent", RuntimeArgs.fromMap({
+      "contract": sample,
+      "entry_points": CLValueBuilder.list(entryPoints.map((entryPoint) => CLValueBuilder.string(entryPoint))),
+      "expiry": CLValueBuilder.option(None, CLTypeBuilder.u64()),
+      "max_uses": CLValueBuilder.option(None, CLTypeBuilder.u64()),
+      "max_spend": maxSpend === undefined
+        ? CLValueBuilder.option(None, CLTypeBuilder.u512())
+        : CLValueBuilder.option(Some(CLValueBuilder.u512(maxSpend))),
+    }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]);
+  const setSubsidy = (allowance: number | undefined) => contractClient.callEntrypoint("set_subsidy", RuntimeArgs.fromMap({
+    "contract": sample,
+    "allowance": allowance === undefined
+      ? CLValueBuilder.option(None, CLTypeBuilder.u512())
+      : CLValueBuilder.option(Some(CLValueBuilder.u512(allowance))),
+    "period": CLValueBuilder.u64(0),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]);
+
+  // Relayers may only call the entry points the caller consented to
+  console.log('*** Consent to another entry point ***');
+  await sendDeploy(setConsent(["post_relay"], undefined));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "Without consent", MOTE_RATE),
+    userError(RelayError.ConsentMissing));
+
+  // With no subsidy the caller pays everything, up to 1.53 CSPR here: one call of 1 CSPR with its fee
+  console.log('*** Cap what relayers may charge the caller ***');
+  await sendDeploy(setSubsidy(0));
+  await sendDeploy(setConsent(["set_message"], 1_530_000_000));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Within the spend limit", MOTE_RATE));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "Over the spend limit", MOTE_RATE),
+    userError(RelayError.SpendLimitExceeded));
+
+  await sendDeploy(setSubsidy(undefined));
+  await sendDeploy(setConsent(["set_message"], undefined));
+}
+
+async function testResult() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const cep18Hash = await getAccountNamedKeyValue(accountInfo, "cep18_contract_hash_USDT");
+  const cep18 = new CEP18Client(process.env.NODE_URL!, process.env.CHAIN_NAME!);
+  cep18.setContractHash(cep18Hash);
+  contractClient.setContractHash(relayContractHash)
+  const token = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(cep18Hash.slice(5)));
+
+  // The token's balance_of returns a U256, relayed back instead of making the relay revert
+  console.log('*** Register token ***');
+  await sendDeploy(contractClient.callEntrypoint("register", RuntimeArgs.fromMap({
+    "contract": token,
+    "owner": accountKey(USER1_KEYS),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+  await sendDeploy(contractClient.callEntrypoint("set_consent", RuntimeArgs.fromMap({
+    "contract": token,
+    "entry_points": CLValueBuilder.list([CLValueBuilder.string("balance_of")]),
+    "expiry": CLValueBuilder.option(None, CLTypeBuilder.u64()),
+    "max_uses": CLValueBuilder.option(None, CLTypeBuilder.u64()),
+    "max_spend": CLValueBuilder.option(None, CLTypeBuilder.u512()),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+
+  const balance = await cep18.balanceOf(USER1_KEYS.publicKey);
+  console.log('*** Read balance through relay ***');
+  await sendDeploy(contractClient.callEntrypoint("call_on_behalf", RuntimeArgs.fromMap({
+    "contract": token,
+    "entry_point": CLValueBuilder.string("balance_of"),
+    "caller": accountKey(USER1_KEYS),
+    "gas_amount": CLValueBuilder.u512(100_000_000),
+    "pay_amount": CLValueBuilder.u512(0),
+    "args": CLValueBuilder.byteArray(RuntimeArgs.fromMap({
+      address: accountKey(USER1_KEYS)
+    }).toBytes().unwrap())
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+
+  // The event carries the hash of the returned value
+  const event = await getCallOnBehalfEvent(contractClient);
+  const resultHash = Buffer.from(blake2b(CLValueBuilder.u256(balance).toBytes().unwrap(), {dkLen: 32})).toString('hex');
+  assert.equal(event.resultHash, resultHash);
+}
+
+async function testBatch() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+
+  // Each call carries its own (args, pay_amount), the gas is given once for the whole batch
+  const batchCall = (message: string) => CLValueBuilder.tuple3([
+    CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5))),
+    CLValueBuilder.string("set_message"),
+    CLValueBuilder.tuple2([
+      CLValueBuilder.list(Array.from(RuntimeArgs.fromMap({
+        message: CLValueBuilder.string(message)
+      }).toBytes().unwrap()).map((byte) => CLValueBuilder.u8(byte))),
+      CLValueBuilder.u512(0),
+    ]),
+  ]);
+
+  const balanceBefore = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  console.log('*** Set messages in a batch ***');
+  await sendDeploy(contractClient.callEntrypoint("call_on_behalf_batch", RuntimeArgs.fromMap({
+    "calls": CLValueBuilder.list([
+      batchCall("Hello from batch 1"),
+      batchCall("Hello from batch 2"),
+    ]),
+    "caller": accountKey(USER1_KEYS),
+    "gas_amount": CLValueBuilder.u512(2 * MOTE_RATE),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(20 * MOTE_RATE), [FAUCET_KEYS]));
+  const balanceAfter = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceAfter.toString(), balanceBefore.sub(2_040_000_000).toString());
+
+  contractClient.setContractHash(sampleContractHash)
+  assert.equal(await contractClient.queryContractData(["message"]), "Hello from batch 2");
+}
+
+async function testPackage() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  const samplePackageHash = await getAccountNamedKeyValue(accountInfo, "sample_package_name");
+  console.log("Sample contract package hash: ", samplePackageHash)
+  contractClient.setContractHash(relayContractHash)
+  const samplePackage = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(samplePackageHash.slice(5)));
+
+  // Registered pinned to the first version
+  console.log('*** Register package ***');
+  await sendDeploy(contractClient.callEntrypoint("register_package", RuntimeArgs.fromMap({
+    "contract_package": samplePackage,
+    "owner": accountKey(USER1_KEYS),
+    "version": CLValueBuilder.option(Some(CLValueBuilder.u32(1))),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+  await sendDeploy(contractClient.callEntrypoint("set_consent", RuntimeArgs.fromMap({
+    "contract": samplePackage,
+    "entry_points": CLValueBuilder.list([CLValueBuilder.string("set_message")]),
+    "expiry": CLValueBuilder.option(None, CLTypeBuilder.u64()),
+    "max_uses": CLValueBuilder.option(None, CLTypeBuilder.u64()),
+    "max_spend": CLValueBuilder.option(None, CLTypeBuilder.u512()),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+
+  // The package hash is passed as the contract
+  const balanceBefore = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  console.log('*** Set message through package ***');
+  await sendDeploy(setMessageDeploy(contractClient, samplePackage, "Hello through package", MOTE_RATE));
+  const balanceAfter = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceAfter.toString(), balanceBefore.sub(1_020_000_000).toString());
+  contractClient.setContractHash(sampleContractHash)
+  assert.equal(await contractClient.queryContractData(["message"]), "Hello through package");
+  contractClient.setContractHash(relayContractHash)
+
+  // Registering again without a version keeps the pin
+  console.log('*** Register package again ***');
+  await sendDeploy(contractClient.callEntrypoint("register_package", RuntimeArgs.fromMap({
+    "contract_package": samplePackage,
+    "owner": accountKey(USER1_KEYS),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+  const version = await contractClient.queryContractDictionary("package_version", samplePackageHash.slice(5));
+  assert.equal((version.value() as Option<CLU32>).unwrap().value().toNumber(), 1);
+
+  // Denying the package hash refuses calls to it whichever version they resolve to
+  console.log('*** Deny package ***');
+  const updateDeniedPackages = (add: CLValue, remove: CLValue) =>
+    contractClient.callEntrypoint("update_denied_packages", RuntimeArgs.fromMap({
+      "add": add,
+      "remove": remove,
+    }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]);
+  await sendDeploy(updateDeniedPackages(CLValueBuilder.list([samplePackage]), new CLList(CLTypeBuilder.byteArray(32))));
+  await rejectsWith(setMessageDeploy(contractClient, samplePackage, "To a denied package", MOTE_RATE),
+    userError(RelayError.Denied));
+  await sendDeploy(updateDeniedPackages(new CLList(CLTypeBuilder.byteArray(32)), CLValueBuilder.list([samplePackage])));
+}
+
+async function testInjectedArgs() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  const setInjectedArgs = (fields: Array<[number, string]>) => contractClient.callEntrypoint("set_injected_args", RuntimeArgs.fromMap({
+    "contract": sample,
+    "fields": CLValueBuilder.list(fields.map(([field, name]) =>
+      CLValueBuilder.tuple2([CLValueBuilder.u8(field), CLValueBuilder.string(name)]))),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]);
+
+  // By default the caller is injected as `caller`, which the relayer may not set itself
+  console.log('*** Relay a caller argument ***');
+  await rejectsWith(setMessageDeploy(contractClient, sample, "With a caller argument", MOTE_RATE, {args: {caller: accountKey(USER2_KEYS)}}),
+    /InvalidArgument/);
+
+  // Injecting the nonce and relay package instead leaves `caller` to the target
+  console.log('*** Inject nonce and relay package ***');
+  await sendDeploy(setInjectedArgs([[3, "relay_nonce"], [4, "relay_package"]]));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "With its own caller argument", MOTE_RATE, {args: {caller: accountKey(USER2_KEYS)}}));
+  contractClient.setContractHash(sampleContractHash)
+  assert.equal(await contractClient.queryContractData(["message"]), "With its own caller argument");
+  assert.equal(await contractClient.queryContractData(["caller"]), USER1_KEYS.publicKey.toAccountHashStr());
+
+  contractClient.setContractHash(relayContractHash)
+  await sendDeploy(setInjectedArgs([[0, "caller"]]));
+}
+
+async function testContext() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  // With nothing injected the sample still learns the caller from current_context
+  console.log('*** Inject nothing ***');
+  await sendDeploy(contractClient.callEntrypoint("set_injected_args", RuntimeArgs.fromMap({
+    "contract": sample,
+    "fields": new CLList(CLTypeBuilder.tuple2([CLTypeBuilder.u8(), CLTypeBuilder.string()])),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Hello from context", MOTE_RATE));
+  contractClient.setContractHash(sampleContractHash)
+  assert.equal(await contractClient.queryContractData(["message"]), "Hello from context");
+  assert.equal(await contractClient.queryContractData(["caller"]), USER1_KEYS.publicKey.toAccountHashStr());
+
+  // The context only lasts for the relayed call
+  contractClient.setContractHash(relayContractHash)
+  const context = await contractClient.queryContractData(["context"]);
+  assert.ok((context as Option<CLValue>).none);
+
+  await sendDeploy(contractClient.callEntrypoint("set_injected_args", RuntimeArgs.fromMap({
+    "contract": sample,
+    "fields": CLValueBuilder.list([CLValueBuilder.tuple2([CLValueBuilder.u8(0), CLValueBuilder.string("caller")])]),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+}
+
+async function testCallerKey() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  const samplePackageHash = await getAccountNamedKeyValue(accountInfo, "sample_package_name");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  // Contract package callers get as far as the consent check, which they must pass like accounts
+  console.log('*** Relay for a contract package caller ***');
+  const packageCaller = CLValueBuilder.key(CLValueBuilder.byteArray(Contracts.contractHashToByteArray(samplePackageHash.slice(5))));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "From a contract", MOTE_RATE, {caller: packageCaller}),
+    userError(RelayError.ConsentMissing));
+
+  // Other keys aren't callers, here a purse with no access rights
+  console.log('*** Relay for a purse caller ***');
+  const purseCaller = CLValueBuilder.key(CLURef.fromFormattedStr(accountInfo.mainPurse.replace(/-\d{3}$/, "-000")));
+  await rejectsWith(setMessageDeploy(contractClient, sample, "From a purse", MOTE_RATE, {caller: purseCaller}),
+    /InvalidArgument/);
+
+  // The event and the target see the same caller key
+  console.log('*** Relay for an account caller ***');
+  await sendDeploy(setMessageDeploy(contractClient, sample, "From an account", MOTE_RATE));
+  const event = await getCallOnBehalfEvent(contractClient);
+  contractClient.setContractHash(sampleContractHash)
+  assert.equal(event.caller, USER1_KEYS.publicKey.toAccountHashStr());
+  assert.equal(await contractClient.queryContractData(["caller"]), event.caller);
+}
+
+async function testRegistration() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  const samplePackageHash = await getAccountNamedKeyValue(accountInfo, "sample_package_name");
+  contractClient.setContractHash(relayContractHash)
+
+  // Only the target, the installer or the current owner may register a contract
+  console.log('*** Register someone else\'s contract ***');
+  await rejectsWith(contractClient.callEntrypoint("register", RuntimeArgs.fromMap({
+    "contract": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5))),
+    "owner": accountKey(USER2_KEYS),
+  }), USER2_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER2_KEYS]),
+    userError(RelayError.Unauthorized));
+  await rejectsWith(contractClient.callEntrypoint("register_package", RuntimeArgs.fromMap({
+    "contract_package": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(samplePackageHash.slice(5))),
+    "owner": accountKey(USER2_KEYS),
+  }), USER2_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER2_KEYS]),
+    userError(RelayError.Unauthorized));
+
+  // The current owner may hand its registration over
+  console.log('*** Re-register as owner ***');
+  await sendDeploy(contractClient.callEntrypoint("register", RuntimeArgs.fromMap({
+    "contract": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5))),
+    "owner": accountKey(USER1_KEYS),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+}
+
+async function testWallet() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  contractClient.setContractHash(relayContractHash);
+
+  // Without an owner the deposit session credits the sender's own wallet
+  const balanceBefore = await getDictionaryBalance(contractClient, "caller_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  console.log('*** Deposit wallet ***');
+  await depositWallet(USER1_KEYS, 10 * MOTE_RATE);
+  const balanceDeposited = await getDictionaryBalance(contractClient, "caller_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceDeposited.toString(), balanceBefore.add(10 * MOTE_RATE).toString());
+
+  console.log('*** Withdraw wallet ***');
+  await sendDeploy(contractClient.callEntrypoint("withdraw_wallet", RuntimeArgs.fromMap({
+    "amount": CLValueBuilder.u512(MOTE_RATE),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+  const balanceWithdrawn = await getDictionaryBalance(contractClient, "caller_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceWithdrawn.toString(), balanceDeposited.sub(MOTE_RATE).toString());
+
+  // USER1's deposit waits in the relay's deposit purse, which USER2 can't pass off as its own
+  console.log('*** Sweep the deposit purse ***');
+  const depositPurse = await getContractPurse(casperClient, relayContractHash, "deposit_purse");
+  const user2Before = await getDictionaryBalance(contractClient, "caller_balance", USER2_KEYS.publicKey.toAccountRawHashStr());
+  await assert.rejects(sendDeploy(contractClient.callEntrypoint("deposit_wallet", RuntimeArgs.fromMap({
+    "purse": CLURef.fromFormattedStr(depositPurse),
+  }), USER2_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER2_KEYS])));
+  const user2After = await getDictionaryBalance(contractClient, "caller_balance", USER2_KEYS.publicKey.toAccountRawHashStr());
+  const user1After = await getDictionaryBalance(contractClient, "caller_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(user2After.toString(), user2Before.toString());
+  assert.equal(user1After.toString(), balanceWithdrawn.toString());
+}
+
+async function testEscrow() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  // With no timeout the caller may reclaim an unsettled payment right away
+  console.log('*** Set escrow ***');
+  await sendDeploy(contractClient.callEntrypoint("set_escrow", RuntimeArgs.fromMap({
+    "contract": sample,
+    "timeout": CLValueBuilder.option(Some(CLValueBuilder.u64(0))),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+
+  const balanceBefore = await getDictionaryBalance(contractClient, "caller_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  const requestId = await getNonce(contractClient);
+  console.log('*** Set message with escrowed payment ***');
+  await sendDeploy(setMessageDeploy(contractClient, sample, "Hello with escrow", MOTE_RATE, {payAmount: MOTE_RATE}));
+  const balanceHeld = await getDictionaryBalance(contractClient, "caller_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceHeld.toString(), balanceBefore.sub(MOTE_RATE).toString());
+
+  // Only the paying caller may reclaim it
+  console.log('*** Reclaim payment ***');
+  const reclaimDeploy = (keys: Keys.AsymmetricKey) => contractClient.callEntrypoint("reclaim_payment", RuntimeArgs.fromMap({
+    "request_id": CLValueBuilder.u64(requestId),
+  }), keys.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [keys]);
+  await rejectsWith(reclaimDeploy(USER2_KEYS), userError(RelayError.Unauthorized));
+  await sendDeploy(reclaimDeploy(USER1_KEYS));
+  const balanceReclaimed = await getDictionaryBalance(contractClient, "caller_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceReclaimed.toString(), balanceBefore.toString());
+
+  await sendDeploy(contractClient.callEntrypoint("set_escrow", RuntimeArgs.fromMap({
+    "contract": sample,
+    "timeout": CLValueBuilder.option(None, CLTypeBuilder.u64()),
+  }), USER1_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [USER1_KEYS]));
+}
+
+async function testReservation() {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  const sampleContractHash = await getAccountNamedKeyValue(accountInfo, "sample_hash");
+  contractClient.setContractHash(relayContractHash)
+  const sample = CLValueBuilder.byteArray(Contracts.contractHashToByteArray(sampleContractHash.slice(5)));
+
+  const reserveDeploy = (message: string, relayer: Keys.AsymmetricKey) =>
+    setMessageDeploy(contractClient, sample, message, 3 * MOTE_RATE, {entryPoint: "reserve", relayer});
+  const requestDeploy = (entryPoint: string, requestId: number, relayer: Keys.AsymmetricKey, gasUsed?: number) =>
+    contractClient.callEntrypoint(entryPoint, RuntimeArgs.fromMap({
+      "request_id": CLValueBuilder.u64(requestId),
+      ...(gasUsed === undefined ? {} : {"gas_used": CLValueBuilder.u512(gasUsed)}),
+    }), relayer.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [relayer]);
+  const updateRelayers = (add: CLValue, remove: CLValue) =>
+    contractClient.callEntrypoint("update_relayers", RuntimeArgs.fromMap({
+      "add": add,
+      "remove": remove,
+    }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]);
+
+  // Reserve, run and settle for less gas than reserved
+  const balanceBefore = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  const requestId = await getNonce(contractClient);
+  console.log('*** Reserve ***');
+  await sendDeploy(reserveDeploy("Hello from reservation", FAUCET_KEYS));
+  const balanceReserved = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceReserved.toString(), balanceBefore.sub(3_060_000_000).toString());
+
+  console.log('*** Call reserved ***');
+  await rejectsWith(requestDeploy("call_reserved", requestId, USER2_KEYS), userError(RelayError.Unauthorized));
+  await sendDeploy(requestDeploy("call_reserved", requestId, FAUCET_KEYS));
+  contractClient.setContractHash(sampleContractHash)
+  assert.equal(await contractClient.queryContractData(["message"]), "Hello from reservation");
+  assert.equal(await contractClient.queryContractData(["caller"]), USER1_KEYS.publicKey.toAccountHashStr());
+  contractClient.setContractHash(relayContractHash)
+
+  console.log('*** Settle ***');
+  await sendDeploy(requestDeploy("settle", requestId, FAUCET_KEYS, MOTE_RATE));
+  const balanceSettled = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceSettled.toString(), balanceBefore.sub(1_020_000_000).toString());
+
+  // A relayer removed since it reserved can't run the call, and settling it pays nothing
+  console.log('*** Reserve as a relayer removed afterwards ***');
+  const user2 = CLValueBuilder.byteArray(USER2_KEYS.publicKey.toAccountHash());
+  await sendDeploy(updateRelayers(CLValueBuilder.list([user2]), new CLList(CLTypeBuilder.byteArray(32))));
+  const removedId = await getNonce(contractClient);
+  await sendDeploy(reserveDeploy("Never run", USER2_KEYS));
+  await sendDeploy(updateRelayers(new CLList(CLTypeBuilder.byteArray(32)), CLValueBuilder.list([user2])));
+  await rejectsWith(requestDeploy("call_reserved", removedId, USER2_KEYS), userError(RelayError.Unauthorized));
+  await sendDeploy(requestDeploy("settle", removedId, USER2_KEYS, 3 * MOTE_RATE));
+  const balanceUnrun = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceUnrun.toString(), balanceSettled.toString());
+
+  // A reservation that times out gives the owner everything back
+  console.log('*** Reserve with no timeout ***');
+  await sendDeploy(contractClient.callEntrypoint("set_reservation_timeout", RuntimeArgs.fromMap({
+    "timeout": CLValueBuilder.u64(0),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+  const expiredId = await getNonce(contractClient);
+  await sendDeploy(reserveDeploy("Never sent", FAUCET_KEYS));
+  await rejectsWith(requestDeploy("call_reserved", expiredId, FAUCET_KEYS), userError(RelayError.ReservationExpired));
+
+  console.log('*** Expire reservation ***');
+  await sendDeploy(requestDeploy("expire_reservation", expiredId, FAUCET_KEYS));
+  const balanceExpired = await getDictionaryBalance(contractClient, "owner_balance", USER1_KEYS.publicKey.toAccountRawHashStr());
+  assert.equal(balanceExpired.toString(), balanceSettled.toString());
+
+  await sendDeploy(contractClient.callEntrypoint("set_reservation_timeout", RuntimeArgs.fromMap({
+    "timeout": CLValueBuilder.u64(3_600_000),
+  }), FAUCET_KEYS.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [FAUCET_KEYS]));
+}
+
+// Builds a relayed set_message deploy, by default relayed by the faucet for USER1
+function setMessageDeploy(
+  contractClient: Contracts.Contract,
+  contract: CLByteArray,
+  message: string,
+  gasAmount: number,
+  options: {
+    entryPoint?: string,
+    relayer?: Keys.AsymmetricKey,
+    caller?: CLKey,
+    payAmount?: number,
+    args?: Record<string, CLValue>,
+  } = {}
+) {
+  const relayer = options.relayer ?? FAUCET_KEYS;
+  return contractClient.callEntrypoint(options.entryPoint ?? "call_on_behalf", RuntimeArgs.fromMap({
+    "contract": contract,
+    "entry_point": CLValueBuilder.string("set_message"),
+    "caller": options.caller ?? accountKey(USER1_KEYS),
+    "gas_amount": CLValueBuilder.u512(gasAmount),
+    "pay_amount": CLValueBuilder.u512(options.payAmount ?? 0),
+    "args": CLValueBuilder.byteArray(RuntimeArgs.fromMap({
+      message: CLValueBuilder.string(message),
+      ...options.args,
+    }).toBytes().unwrap())
+  }), relayer.publicKey, process.env.NETWORK_NAME!, String(10 * MOTE_RATE), [relayer]);
+}
+
+// Funds the account's own relay wallet through the deposit session
+async function depositWallet(keys: Keys.AsymmetricKey, amount: number) {
+  const casperClient = new CasperClient(process.env.NODE_URL!)
+  const contractClient = new Contract(casperClient);
+  const accountInfo = await getAccountInfo(process.env.NODE_URL!, FAUCET_KEYS.publicKey);
+  const relayContractHash = await getAccountNamedKeyValue(accountInfo, "relay_hash");
+  await sendDeploy(contractClient.install(getBinary('./contracts/deposit.wasm'), RuntimeArgs.fromMap({
+    "relay_contract": CLValueBuilder.byteArray(Contracts.contractHashToByteArray(relayContractHash.slice(5))),
+    "amount": CLValueBuilder.u512(amount),
+  }), String(10 * MOTE_RATE), keys.publicKey, process.env.NETWORK_NAME!, [keys]));
+}
+
+function accountKey(keys: Keys.AsymmetricKey) {
+  return CLValueBuilder.key(new CLAccountHash(keys.publicKey.toAccountHash()));
+}
+
+async function sendDeploy(deploy: DeployUtil.Deploy) {
+  await delay(500);
+  const deployHash = await deploy.send(process.env.NODE_URL!);
+  return await getDeploy(process.env.NODE_URL!, deployHash);
+}
+
+async function rejectsWith(deploy: DeployUtil.Deploy, error: RegExp) {
+  await assert.rejects(sendDeploy(deploy), error);
+}
+
+function userError(code: number) {
+  return new RegExp(`User error: ${code}\\b`);
+}
+
+async function getDictionaryBalance(contractClient: Contracts.Contract, dictionary: string, key: string) {
+  try {
+    return (await contractClient.queryContractDictionary(dictionary, key)).value() as BigNumber;
+  } catch {
+    // Missing entries are an empty balance
+    return BigNumber.from(0);
+  }
+}
+
+// Reads the contract's latest event, serialized by the event standard as its name then its fields
+async function getLastEvent(contractClient: Contracts.Contract) {
+  const length = Number((await contractClient.queryContractData(["__events_length"])).toString());
+  const event = await contractClient.queryContractDictionary("__events", String(length - 1));
+  const bytes = Buffer.from((event.value() as CLU8[]).map((byte) => byte.value().toNumber()));
+  let offset = 0;
+  const readString = () => {
+    const size = bytes.readUInt32LE(offset);
+    const value = bytes.toString('utf8', offset + 4, offset + 4 + size);
+    offset += 4 + size;
+    return value;
+  };
+  const readOption = () => bytes[offset++] === 1 ? readString() : undefined;
+  return {name: readString(), readString, readOption};
+}
+
+async function getCallOnBehalfEvent(contractClient: Contracts.Contract) {
+  const event = await getLastEvent(contractClient);
+  assert.equal(event.name, "event_CallOnBehalf");
+  return {
+    contractHash: event.readString(),
+    owner: event.readString(),
+    caller: event.readString(),
+    entryPoint: event.readString(),
+    gasAmount: event.readString(),
+    ownerAmount: event.readString(),
+    callerAmount: event.readString(),
+    cep18Hash: event.readOption(),
+    resultHash: event.readString(),
+  };
 }
 
 async function getFeePurseBalance(casperClient: CasperClient, contractHash: string) {
@@ -287,4 +1432,14 @@ async function getFeePurseBalance(casperClient: CasperClient, contractHash: stri
   const blockState = await casperClient.nodeClient.getBlockState(rootHash, contractHash, []);
   const purseURef = blockState?.Contract?.namedKeys.find((item) => item.name === 'fee_purse')?.key!;
   return await rpcClient.queryBalance(PurseIdentifier.PurseUref, purseURef);
-}
\ No newline at end of file
+}
+
+async function getContractPurse(casperClient: CasperClient, contractHash: string, name: string) {
+  const rootHash = await casperClient.nodeClient.getStateRootHash();
+  const blockState = await casperClient.nodeClient.getBlockState(rootHash, contractHash, []);
+  return blockState?.Contract?.namedKeys.find((item) => item.name === name)?.key!;
+}
+
+async function getNonce(contractClient: Contracts.Contract) {
+  return Number((await contractClient.queryContractData(["nonce"])).toString());
+}